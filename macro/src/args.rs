@@ -2,13 +2,25 @@ use proc_macro2::{Span, TokenStream};
 use quote::TokenStreamExt;
 use syn::Ident;
 
-use crate::ext::bare_type;
+use quote::ToTokens;
+use syn::spanned::Spanned;
+
+use crate::{attrs::AttrsOptions, ext::bare_type, sig};
 
 pub(crate) struct ArgumentOptions {
 	pub(crate) incoming: TokenStream,
 	pub(crate) transforming: TokenStream,
 	pub(crate) forwarding: TokenStream,
 	pub(crate) env: Ident,
+	pub(crate) receiver: Option<Receiver>,
+	/// Types of the arguments as seen from Java, used to build the method signature.
+	pub(crate) java_types: Vec<syn::Type>,
+}
+
+/// How a method receives `self`: the object is looked up by `handle` in the peer registry.
+pub(crate) struct Receiver {
+	pub(crate) handle: Ident,
+	pub(crate) mutable: bool,
 }
 
 fn unpack_pat(pat: syn::Pat) -> Result<TokenStream, syn::Error> {
@@ -20,7 +32,7 @@ fn unpack_pat(pat: syn::Pat) -> Result<TokenStream, syn::Error> {
 		syn::Pat::Reference(r) => {
 			unpack_pat(*r.pat)
 		},
-		_ => Err(syn::Error::new(Span::call_site(), "unsupported argument type")),
+		pat => Err(syn::Error::new_spanned(pat, "unsupported argument pattern, use a plain name")),
 	}
 }
 
@@ -30,77 +42,296 @@ fn type_equals(ty: Box<syn::Type>, search: impl AsRef<str>) -> bool {
 	last.ident == search.as_ref()
 }
 
+/// What a parameter of the exported function stands for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Role {
+	/// The `JNIEnv`, taken as `&mut JNIEnv`.
+	Env,
+	/// The class declaring a static method.
+	Class,
+	/// The object an instance method is called on, either raw, as a `GlobalRef` or converted through `FromJava`.
+	This,
+	/// The `JavaVM`, injected.
+	Vm,
+	/// The `CallInfo` describing the called method, injected.
+	CallInfo,
+	/// An argument passed from Java, converted through `FromJava`.
+	Argument,
+}
+
+const PARAM_ATTRIBUTES: [&str; 6] = ["env", "this", "class", "vm", "call_info", "jni"];
+
+/// Reads the role declared with `#[env]`, `#[this]`, `#[class]`, `#[vm]` or `#[call_info]`, if any.
+fn explicit_role(attrs: &[syn::Attribute]) -> Result<Option<Role>, syn::Error> {
+	let mut role = None;
+	for attr in attrs {
+		let found = if attr.path().is_ident("env") {
+			Role::Env
+		} else if attr.path().is_ident("this") {
+			Role::This
+		} else if attr.path().is_ident("class") {
+			Role::Class
+		} else if attr.path().is_ident("vm") {
+			Role::Vm
+		} else if attr.path().is_ident("call_info") {
+			Role::CallInfo
+		} else {
+			continue
+		};
+		attr.meta.require_path_only()?;
+		if role.is_some() {
+			return Err(syn::Error::new_spanned(attr, "a parameter can only have one role"));
+		}
+		role = Some(found);
+	}
+	Ok(role)
+}
+
+/// Reads the module converting a parameter, declared with `#[jni(with = path::to::module)]`, if any.
+fn conversion(attrs: &[syn::Attribute]) -> Result<Option<syn::Path>, syn::Error> {
+	let mut with = None;
+	for attr in attrs.iter().filter(|a| a.path().is_ident("jni")) {
+		attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("with") {
+				with = Some(crate::attrs::path(&meta)?);
+				Ok(())
+			} else {
+				Err(meta.error("expected `with = path::to::module`"))
+			}
+		})?;
+	}
+	Ok(with)
+}
+
+/// Removes the parameter attributes, as they only have meaning to `#[jni]`.
+pub(crate) fn strip_param_attrs(sig: &mut syn::Signature) {
+	for arg in sig.inputs.iter_mut() {
+		if let syn::FnArg::Typed(ty) = arg {
+			ty.attrs.retain(|a| !PARAM_ATTRIBUTES.iter().any(|r| a.path().is_ident(r)));
+		}
+	}
+}
+
 impl ArgumentOptions {
-	pub(crate) fn parse_args(fn_item: &syn::ItemFn, ret_expr: TokenStream) -> Result<Self, syn::Error> {
-		let mut arguments = Vec::new();
-		let mut pass_env = false;
-		let mut pass_class = false;
+	pub(crate) fn parse_args(fn_item: &syn::ItemFn, attrs: &AttrsOptions, ret_expr: TokenStream) -> Result<Self, syn::Error> {
+		let mut params = Vec::new();
+		let mut receiver = None;
 		for arg in fn_item.sig.inputs.iter() {
-			let syn::FnArg::Typed(ty) = arg else {
-				return Err(syn::Error::new(Span::call_site(), "#[jni] macro doesn't work on methods"));
+			let ty = match arg {
+				syn::FnArg::Typed(ty) => ty,
+				syn::FnArg::Receiver(r) => {
+					if r.reference.is_none() {
+						return Err(syn::Error::new_spanned(r, "#[jni] methods must take either &self or &mut self"));
+					}
+					receiver = Some(Receiver {
+						handle: syn::Ident::new("peer_handle", Span::mixed_site()),
+						mutable: r.mutability.is_some(),
+					});
+					continue;
+				},
 			};
 			let pat = unpack_pat(*ty.pat.clone())?;
-			if type_equals(ty.ty.clone(), "JNIEnv") { pass_env = true };
-			if type_equals(ty.ty.clone(), "JClass") { pass_class = true };
-			arguments.push(SingleArgument {
-				pat: syn::Ident::new(&pat.to_string(), Span::call_site()),
+			let role = explicit_role(&ty.attrs)?;
+			let with = conversion(&ty.attrs)?;
+			if let (Some(_), Some(with)) = (role, &with) {
+				return Err(syn::Error::new_spanned(with, "parameters filled in by the wrapper can't be converted with a module"));
+			}
+			params.push(SingleArgument {
+				pat: syn::Ident::new(&pat.to_string(), ty.pat.span()),
 				ty: ty.ty.clone(),
+				role,
+				with,
 			});
 		}
 
+		// without explicit roles, they are recognized by type (and `this` by name), otherwise nothing is guessed
+		let guess = params.iter().all(|p| p.role.is_none());
+		let guess_class = guess && receiver.is_none() && !attrs.instance();
+		let mut seen = Vec::new();
+		for param in params.iter_mut() {
+			let role = match param.role {
+				Some(role) => role,
+				None if !guess || param.with.is_some() => Role::Argument,
+				None if type_equals(param.ty.clone(), "JNIEnv") => Role::Env,
+				None if guess_class && type_equals(param.ty.clone(), "JClass") => Role::Class,
+				// `this` is the receiver when it's raw, or whenever the method is marked as instance
+				None if param.pat == "this" && (attrs.instance() || type_equals(param.ty.clone(), "JObject")) => Role::This,
+				None if type_equals(param.ty.clone(), "JavaVM") => Role::Vm,
+				None if type_equals(param.ty.clone(), "CallInfo") => Role::CallInfo,
+				None => Role::Argument,
+			};
+			if role != Role::Argument && seen.contains(&role) {
+				return Err(syn::Error::new(param.ty.span(), "this role was already given to another parameter"));
+			}
+			seen.push(role);
+			param.role = Some(role);
+		}
+		let find = |role: Role| params.iter().find(|p| p.role == Some(role));
+
+		if let Some(class) = find(Role::Class) {
+			if receiver.is_some() {
+				return Err(syn::Error::new(class.ty.span(), "#[jni] methods can't receive the Java class"));
+			}
+			if find(Role::This).is_some() || attrs.instance() {
+				return Err(syn::Error::new(class.ty.span(), "instance methods can't receive the Java class"));
+			}
+		}
+		if let (Some(this), true) = (find(Role::This), receiver.is_some() && attrs.handle) {
+			return Err(syn::Error::new(this.ty.span(), "methods using a handle are static, and can't receive this"));
+		}
+
 		let mut incoming = TokenStream::new();
 		let mut transforming = TokenStream::new();
 		let mut forwarding = TokenStream::new();
+		let mut java_types = Vec::new();
 
-		let env = if pass_env {
-			arguments.first()
-				.ok_or_else(|| syn::Error::new(Span::call_site(), "missing env parameter"))?
-				.pat
-				.clone()
-		} else {
-			syn::Ident::new("env", Span::call_site())
+		let env = match find(Role::Env) {
+			Some(param) => param.pat.clone(),
+			// generated names can't clash with the user's ones
+			None => syn::Ident::new("env", Span::mixed_site()),
 		};
+		incoming.append_all(quote::quote!( mut #env: jni::JNIEnv<'local>,));
 
-		let mut args_iter = arguments.into_iter();
-		
-		if pass_env {
-			if let Some(arg) = args_iter.next() {
-				let pat = arg.pat;
-				let ty = bare_type(arg.ty);
-				incoming.append_all(quote::quote!( mut #pat: #ty,));
-				forwarding.append_all(quote::quote!( &mut #pat,));
-			}
-		} else {
-			incoming.append_all(quote::quote!( mut #env: jni::JNIEnv<'local>,));
+		let this = syn::Ident::new("this", Span::mixed_site());
+		let class_arg = syn::Ident::new("_class", Span::mixed_site());
+		match (&receiver, find(Role::Class)) {
+			(Some(Receiver { ref handle, .. }), _) if !attrs.handle => {
+				let field = attrs.field.as_deref().unwrap_or("ptr");
+				incoming.append_all(quote::quote!( #this: jni::objects::JObject<'local>,));
+				transforming.append_all(quote::quote!{
+					let #handle = match #env.get_field(&#this, #field, "J").and_then(|x| x.j()) {
+						Ok(x) => x,
+						Err(e) => {
+							jni_toolbox::error::throw_error(&mut #env, &e);
+							return #ret_expr;
+						},
+					};
+				});
+			},
+			(Some(Receiver { ref handle, .. }), _) => {
+				incoming.append_all(quote::quote!( #class_arg: jni::objects::JClass<'local>, #handle: jni::sys::jlong,));
+				java_types.push(syn::parse_quote!(jni::sys::jlong));
+			},
+			(None, Some(class)) => {
+				let pat = &class.pat;
+				incoming.append_all(quote::quote!( #pat: jni::objects::JClass<'local>,));
+			},
+			(None, None) if find(Role::This).is_some() || attrs.instance() => {
+				incoming.append_all(quote::quote!( #this: jni::objects::JObject<'local>,));
+			},
+			(None, None) => {
+				incoming.append_all(quote::quote!( #class_arg: jni::objects::JClass<'local>,));
+			},
 		}
 
-		if !pass_class {
-			incoming.append_all(quote::quote!( _class: jni::objects::JClass<'local>,));
-		}
-
-		for arg in args_iter {
-			let pat = arg.pat;
-			let new_pat = syn::Ident::new(&format!("{pat}_new"), Span::call_site());
-			let ty = arg.ty;
-			transforming.append_all(quote::quote!{
-				let #new_pat = match jni_toolbox::from_java_static::<#ty>(&mut #env, #pat) {
-					Ok(x) => x,
-					Err(e) => {
-						// TODO should we panic here instead?
-						let _ = #env.throw_new(e.jclass(), format!("{e:?}"));
-						return #ret_expr;
-					},
-				};
-			});
-			incoming.append_all(quote::quote!( #pat: <#ty as jni_toolbox::FromJava<'local>>::From,));
-			forwarding.append_all(quote::quote!( #new_pat,));
+		let java_name = attrs.name.clone().unwrap_or_else(|| fn_item.sig.ident.to_string());
+		let ident = &fn_item.sig.ident;
+		let class = match attrs.package(ident)? {
+			"" => attrs.class(ident)?.to_string(),
+			package => format!("{package}/{}", attrs.class(ident)?),
+		};
+		let receiver_type = class.replace(['/', '$'], ".");
+		let method = format!("{receiver_type}.{java_name}");
+		for param in params {
+			let pat = param.pat;
+			let new_pat = syn::Ident::new(&format!("{pat}_new"), Span::mixed_site());
+			let ty = param.ty;
+			let by_ref = matches!(*ty, syn::Type::Reference(_));
+			match param.role.unwrap_or(Role::Argument) {
+				Role::Env => forwarding.append_all(quote::quote!( &mut #env,)),
+				Role::Class if by_ref => forwarding.append_all(quote::quote!( &#pat,)),
+				Role::Class => forwarding.append_all(quote::quote!( #pat,)),
+				Role::This if type_equals(ty.clone(), "GlobalRef") => {
+					transforming.append_all(quote::quote!{
+						let #new_pat = match #env.new_global_ref(&#this) {
+							Ok(x) => x,
+							Err(e) => {
+								jni_toolbox::error::throw_error(&mut #env, &e);
+								return #ret_expr;
+							},
+						};
+					});
+					forwarding.append_all(if by_ref { quote::quote!( &#new_pat,) } else { quote::quote!( #new_pat,) });
+				},
+				Role::This if type_equals(ty.clone(), "JObject") && by_ref => forwarding.append_all(quote::quote!( &#this,)),
+				Role::This if type_equals(ty.clone(), "JObject") => forwarding.append_all(quote::quote!( #this,)),
+				Role::This => {
+					let convert = quote::quote_spanned!(ty.span()=> jni_toolbox::from_java_static::<#ty>);
+					let name = pat.to_string();
+					transforming.append_all(quote::quote!{
+						let #new_pat = match #convert(&mut #env, #this.into()) {
+							Ok(x) => x,
+							Err(e) => {
+								let e = jni_toolbox::error::ArgumentError { method: #method, position: 0, name: #name, java_type: #receiver_type, source: e };
+								jni_toolbox::error::throw_error(&mut #env, &e);
+								return #ret_expr;
+							},
+						};
+					});
+					forwarding.append_all(quote::quote!( #new_pat,));
+				},
+				Role::Vm => {
+					transforming.append_all(quote::quote!{
+						let #new_pat = match #env.get_java_vm() {
+							Ok(x) => x,
+							Err(e) => {
+								jni_toolbox::error::throw_error(&mut #env, &e);
+								return #ret_expr;
+							},
+						};
+					});
+					forwarding.append_all(if by_ref { quote::quote!( &#new_pat,) } else { quote::quote!( #new_pat,) });
+				},
+				Role::CallInfo => {
+					transforming.append_all(quote::quote!{
+						let #new_pat = jni_toolbox::CallInfo { class: #class, method: #java_name };
+					});
+					forwarding.append_all(if by_ref { quote::quote!( &#new_pat,) } else { quote::quote!( #new_pat,) });
+				},
+				Role::Argument => {
+					let position = java_types.len() + 1;
+					let name = pat.to_string();
+					let (convert, from, java_type) = match param.with {
+						Some(with) => (
+							quote::quote!( #with::from_java ),
+							quote::quote!( #with::From<'local> ),
+							// the module's Java type is unknown here, so signatures can't be inferred
+							syn::parse_quote!( #with::From ),
+						),
+						// spanned on the type, so that a missing conversion is reported on it
+						None => (
+							quote::quote_spanned!(ty.span()=> jni_toolbox::from_java_static::<#ty>),
+							quote::quote_spanned!(ty.span()=> <#ty as jni_toolbox::FromJava<'local>>::From ),
+							*ty.clone(),
+						),
+					};
+					let java_type_name = sig::java_type_name(&java_type).unwrap_or_else(|| ty.to_token_stream().to_string());
+					transforming.append_all(quote::quote!{
+						let #new_pat = match #convert(&mut #env, #pat) {
+							Ok(x) => x,
+							Err(e) => {
+								let e = jni_toolbox::error::ArgumentError { method: #method, position: #position, name: #name, java_type: #java_type_name, source: e };
+								jni_toolbox::error::throw_error(&mut #env, &e);
+								return #ret_expr;
+							},
+						};
+					});
+					incoming.append_all(quote::quote!( #pat: #from,));
+					java_types.push(java_type);
+					forwarding.append_all(quote::quote!( #new_pat,));
+				},
+			}
 		}
 
-		Ok(Self { incoming, transforming, forwarding, env })
+		Ok(Self { incoming, transforming, forwarding, env, receiver, java_types })
 	}
 }
 
 struct SingleArgument {
 	pat: syn::Ident,
 	ty: Box<syn::Type>,
+	/// Set by the role attributes, then filled in by recognizing types.
+	role: Option<Role>,
+	/// The module converting this argument, instead of `FromJava`.
+	with: Option<syn::Path>,
 }
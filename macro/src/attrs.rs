@@ -1,66 +1,337 @@
-use proc_macro2::{Span, TokenStream, TokenTree};
+use std::collections::HashMap;
 
+use proc_macro2::{Span, TokenStream};
+use syn::{meta::ParseNestedMeta, punctuated::Punctuated, spanned::Spanned, LitStr};
+
+#[derive(Clone, Default)]
 pub(crate) struct AttrsOptions {
-	pub(crate) package: String,
-	pub(crate) class: String,
+	pub(crate) package: Option<String>,
+	pub(crate) class: Option<String>,
 	pub(crate) exception: Option<String>,
 	pub(crate) inline: bool,
+	pub(crate) field: Option<String>,
+	pub(crate) handle: bool,
+	pub(crate) instance: Option<bool>,
+	pub(crate) skip: bool,
+	pub(crate) name: Option<String>,
+	pub(crate) overload: bool,
+	pub(crate) signature: Option<LitStr>,
+	pub(crate) ret_with: Option<syn::Path>,
+	pub(crate) instantiations: Vec<Instantiation>,
+	pub(crate) targets: Vec<ExportTarget>,
+	pub(crate) register: Option<bool>,
+	pub(crate) panic: Option<PanicPolicy>,
+	pub(crate) errors: Vec<ErrorMapping>,
+	pub(crate) message: Option<MessageFormat>,
+	pub(crate) errors_as_values: Option<bool>,
+	/// Where each option was given, to report errors on it.
+	pub(crate) spans: HashMap<String, Span>,
+}
+
+/// Maps a foreign error type onto a Java exception class, as in `errors(std::fmt::Error => "java.io.IOException")`.
+#[derive(Clone)]
+pub(crate) struct ErrorMapping {
+	pub(crate) ty: syn::Type,
+	pub(crate) class: String,
+}
+
+impl syn::parse::Parse for ErrorMapping {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let ty = input.parse()?;
+		input.parse::<syn::Token![=>]>()?;
+		let class = java_class(&input.parse()?)?;
+		Ok(Self { ty, class })
+	}
+}
+
+/// An additional class or name to export a function to, as in `target(class = "Compat", name = "legacyName")`.
+/// Options left unspecified are the same as the function's.
+#[derive(Clone, Default)]
+pub(crate) struct ExportTarget {
+	pub(crate) package: Option<String>,
+	pub(crate) class: Option<String>,
+	pub(crate) name: Option<String>,
+	/// Where the name was given, to report errors on it.
+	pub(crate) name_span: Option<Span>,
+}
+
+/// One concrete version of a generic function, as in `instantiate(T = i32, name = "sumInt")`.
+#[derive(Clone)]
+pub(crate) struct Instantiation {
+	/// The type given to each type parameter.
+	pub(crate) types: Vec<(syn::Ident, syn::Type)>,
+	/// The Java name of this version, if different from the function's one.
+	pub(crate) name: Option<String>,
+	pub(crate) span: Span,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum PanicPolicy {
+	Throw,
+	Abort,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum MessageFormat {
+	Display,
+	Debug,
+	Chain,
 }
 
 impl AttrsOptions {
 	pub(crate) fn parse_attr(attrs: TokenStream) -> Result<Self, syn::Error> {
-		let mut what_next = WhatNext::Nothing;
-
-		let mut package = None;
-		let mut class = None;
-		let mut exception = None;
-		let mut inline = false;
-	
-		for attr in attrs {
-			match what_next {
-				WhatNext::Nothing => {
-					if let TokenTree::Ident(ref i) = attr {
-						match i.to_string().as_ref() {
-							"package" => what_next = WhatNext::Package,
-							"class" => what_next = WhatNext::Class,
-							"exception" => what_next = WhatNext::Exception,
-							"ptr" => {}, // accepted for backwards compatibility
-							"inline" => inline = true,
-							_ => return Err(syn::Error::new(Span::call_site(), "unexpected attribute on macro: {attr}")),
-						}
-					}
-				},
-				WhatNext::Class => {
-					if let TokenTree::Literal(i) = attr {
-						class = Some(i.to_string().replace('"', ""));
-						what_next = WhatNext::Nothing;
-					}
-				},
-				WhatNext::Package => {
-					if let TokenTree::Literal(i) = attr {
-						package = Some(i.to_string().replace('"', "").replace(".", "_"));
-						what_next = WhatNext::Nothing;
+		let mut options = Self::default();
+		let parser = syn::meta::parser(|meta| options.parse_meta(meta));
+		syn::parse::Parser::parse2(parser, attrs)?;
+		Ok(options)
+	}
+
+	/// Checks that options only meaning something for a single function aren't set on a whole block.
+	pub(crate) fn reject_item_only(&self) -> Result<(), syn::Error> {
+		let set = [
+			("name", self.name.is_some()),
+			("signature", self.signature.is_some()),
+			("ret_with", self.ret_with.is_some()),
+			("target", !self.targets.is_empty()),
+			("instantiate", !self.instantiations.is_empty()),
+		];
+		match set.into_iter().find(|(_, set)| *set) {
+			Some((key, _)) => Err(syn::Error::new(self.span(key), format!("'{key}' only applies to single functions, not to impl blocks or modules"))),
+			None => Ok(()),
+		}
+	}
+
+	/// Checks options which can't be combined, once inherited from the enclosing block.
+	pub(crate) fn validate(&self) -> Result<(), syn::Error> {
+		if self.field.is_some() && self.handle {
+			return Err(syn::Error::new(self.span("field"), "'field' and 'handle' are mutually exclusive"));
+		}
+
+		if self.instance() && self.handle {
+			return Err(syn::Error::new(self.span("instance"), "'instance' and 'handle' are mutually exclusive"));
+		}
+
+		Ok(())
+	}
+
+	fn parse_meta(&mut self, meta: ParseNestedMeta) -> Result<(), syn::Error> {
+		let Some(key) = meta.path.get_ident() else {
+			return Err(meta.error("expected an attribute name"));
+		};
+		self.spans.insert(key.to_string(), key.span());
+		match key.to_string().as_str() {
+			"package" => self.package = Some(java_package(&string(&meta)?)?),
+			"class" => self.class = Some(java_class_name(&string(&meta)?)?),
+			"exception" => self.exception = Some(java_class(&string(&meta)?)?),
+			"ptr" => {}, // accepted for backwards compatibility
+			"inline" => self.inline = true,
+			"field" => self.field = Some(java_identifier(&string(&meta)?)?),
+			"handle" => self.handle = true,
+			"instance" if meta.input.peek(syn::Token![=]) => self.instance = Some(meta.value()?.parse::<syn::LitBool>()?.value),
+			"instance" => self.instance = Some(true),
+			"skip" => self.skip = true,
+			"name" => self.name = Some(java_identifier(&string(&meta)?)?),
+			"overload" => self.overload = true,
+			"signature" => self.signature = Some(string(&meta)?),
+			"ret_with" => self.ret_with = Some(path(&meta)?),
+			"target" => {
+				let mut target = ExportTarget::default();
+				meta.parse_nested_meta(|inner| {
+					if inner.path.is_ident("package") {
+						target.package = Some(java_package(&string(&inner)?)?);
+					} else if inner.path.is_ident("class") {
+						target.class = Some(java_class_name(&string(&inner)?)?);
+					} else if inner.path.is_ident("name") {
+						target.name = Some(java_identifier(&string(&inner)?)?);
+						target.name_span = Some(inner.path.span());
+					} else {
+						return Err(inner.error("expected `package`, `class` or `name`"));
 					}
-				},
-				WhatNext::Exception => {
-					if let TokenTree::Literal(i) = attr {
-						exception = Some(i.to_string().replace('"', "").replace(".", "_"));
-						what_next = WhatNext::Nothing;
+					Ok(())
+				})?;
+				self.targets.push(target);
+			},
+			"instantiate" => {
+				let mut instantiation = Instantiation { types: Vec::new(), name: None, span: meta.path.span() };
+				meta.parse_nested_meta(|inner| {
+					if inner.path.is_ident("name") {
+						instantiation.name = Some(java_identifier(&string(&inner)?)?);
+					} else if let Some(param) = inner.path.get_ident() {
+						instantiation.types.push((param.clone(), inner.value()?.parse()?));
+					} else {
+						return Err(inner.error("expected `T = Type` or `name = \"...\"`"));
 					}
-				}
-			}
+					Ok(())
+				})?;
+				self.instantiations.push(instantiation);
+			},
+			"register" => self.register = Some(true),
+			"export" => self.register = Some(false),
+			"panic" => {
+				let lit = string(&meta)?;
+				self.panic = Some(match lit.value().as_str() {
+					"throw" => PanicPolicy::Throw,
+					"abort" => PanicPolicy::Abort,
+					_ => return Err(syn::Error::new(lit.span(), "'panic' must be either \"throw\" or \"abort\"")),
+				});
+			},
+			"message" => {
+				let lit = string(&meta)?;
+				self.message = Some(match lit.value().as_str() {
+					"display" => MessageFormat::Display,
+					"debug" => MessageFormat::Debug,
+					"chain" => MessageFormat::Chain,
+					_ => return Err(syn::Error::new(lit.span(), "'message' must be one of \"display\", \"debug\" or \"chain\"")),
+				});
+			},
+			"errors" if meta.input.peek(syn::token::Paren) => {
+				let content;
+				syn::parenthesized!(content in meta.input);
+				self.errors = Punctuated::<ErrorMapping, syn::Token![,]>::parse_terminated(&content)?.into_iter().collect();
+			},
+			"errors" => {
+				let lit = string(&meta)?;
+				self.errors_as_values = Some(match lit.value().as_str() {
+					"value" => true,
+					"throw" => false,
+					_ => return Err(syn::Error::new(lit.span(), "'errors' must be either \"value\", \"throw\" or a list of mappings")),
+				});
+			},
+			_ => return Err(meta.error(format!("unknown attribute '{key}'"))),
+		}
+		Ok(())
+	}
+
+	/// Fills every option left unspecified with the one set on the enclosing `impl` block or module.
+	pub(crate) fn inherit(self, defaults: &AttrsOptions) -> Self {
+		Self {
+			package: self.package.or_else(|| defaults.package.clone()),
+			class: self.class.or_else(|| defaults.class.clone()),
+			exception: self.exception.or_else(|| defaults.exception.clone()),
+			inline: self.inline || defaults.inline,
+			field: self.field.or_else(|| defaults.field.clone()),
+			handle: self.handle || defaults.handle,
+			instance: self.instance.or(defaults.instance),
+			skip: self.skip,
+			name: self.name,
+			overload: self.overload || defaults.overload,
+			signature: self.signature,
+			ret_with: self.ret_with,
+			instantiations: self.instantiations,
+			targets: self.targets,
+			register: self.register.or(defaults.register),
+			panic: self.panic.or(defaults.panic),
+			errors: self.errors.into_iter().chain(defaults.errors.iter().cloned()).collect(),
+			message: self.message.or(defaults.message),
+			errors_as_values: self.errors_as_values.or(defaults.errors_as_values),
+			spans: defaults.spans.clone().into_iter().chain(self.spans).collect(),
+		}
+	}
+
+	/// The options for exporting the function to the given additional target.
+	pub(crate) fn retarget(&self, target: &ExportTarget) -> Self {
+		Self {
+			package: target.package.clone().or_else(|| self.package.clone()),
+			class: target.class.clone().or_else(|| self.class.clone()),
+			name: target.name.clone().or_else(|| self.name.clone()),
+			targets: Vec::new(),
+			..self.clone()
 		}
+	}
+
+	/// Where the given option was set, or the call site if it wasn't.
+	pub(crate) fn span(&self, key: &str) -> Span {
+		self.spans.get(key).copied().unwrap_or_else(Span::call_site)
+	}
+
+	/// Whether the function should be registered through `RegisterNatives` rather than exported.
+	/// This is decided by each crate through its own options, or `[package.metadata.jni-toolbox]` in its
+	/// manifest, as cargo features would leak into other crates.
+	pub(crate) fn register(&self) -> bool {
+		self.register.unwrap_or(false)
+	}
+
+	/// Whether the function implements an instance `native` method.
+	pub(crate) fn instance(&self) -> bool {
+		self.instance.unwrap_or(false)
+	}
+
+	/// The package of the given function, reporting it on its name if missing.
+	pub(crate) fn package(&self, item: &syn::Ident) -> Result<&str, syn::Error> {
+		self.package.as_deref().ok_or_else(|| syn::Error::new(item.span(), "missing required attribute 'package'"))
+	}
 
-		let Some(package) = package else { return Err(syn::Error::new(Span::call_site(), "missing required attribute 'package'")) };
-		let Some(class) = class else { return Err(syn::Error::new(Span::call_site(), "missing required attribute 'class'")) };
+	/// The class of the given function, reporting it on its name if missing.
+	pub(crate) fn class(&self, item: &syn::Ident) -> Result<&str, syn::Error> {
+		self.class.as_deref().ok_or_else(|| syn::Error::new(item.span(), "missing required attribute 'class'"))
+	}
+}
 
-		Ok(Self { package, class, exception, inline })
+/// Parses the string value of an option, as in `key = "value"`.
+fn string(meta: &ParseNestedMeta) -> Result<LitStr, syn::Error> {
+	if !meta.input.peek(syn::Token![=]) {
+		return Err(meta.error("missing value, expected `= \"...\"`"));
 	}
+	meta.value()?.parse()
 }
 
-enum WhatNext {
-	Nothing,
-	Package,
-	Class,
-	Exception,
+/// Parses the path value of an option, as in `key = some::module`.
+pub(crate) fn path(meta: &ParseNestedMeta) -> Result<syn::Path, syn::Error> {
+	if !meta.input.peek(syn::Token![=]) {
+		return Err(meta.error("missing value, expected `= path::to::module`"));
+	}
+	meta.value()?.parse()
+}
+
+const JAVA_KEYWORDS: [&str; 54] = [
+	"abstract", "assert", "boolean", "break", "byte", "case", "catch", "char", "class", "const",
+	"continue", "default", "do", "double", "else", "enum", "extends", "final", "finally", "float",
+	"for", "goto", "if", "implements", "import", "instanceof", "int", "interface", "long", "native",
+	"new", "package", "private", "protected", "public", "return", "short", "static", "strictfp", "super",
+	"switch", "synchronized", "this", "throw", "throws", "transient", "try", "void", "volatile", "while",
+	"true", "false", "null", "_",
+];
+
+/// Whether the given name is a valid Java identifier, `$` included.
+fn is_java_identifier(name: &str) -> bool {
+	let mut chars = name.chars();
+	let Some(first) = chars.next() else { return false };
+	(first.is_alphabetic() || first == '_' || first == '$')
+		&& chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+		&& !JAVA_KEYWORDS.contains(&name)
+}
+
+/// Parses a single Java identifier, such as a method or field name.
+fn java_identifier(lit: &LitStr) -> Result<String, syn::Error> {
+	let name = lit.value();
+	if !is_java_identifier(&name) {
+		return Err(syn::Error::new(lit.span(), format!("'{name}' is not a valid Java identifier")));
+	}
+	Ok(name)
+}
+
+/// Parses a class name inside its package, such as `Outer$Inner`.
+fn java_class_name(lit: &LitStr) -> Result<String, syn::Error> {
+	let name = lit.value();
+	if name.contains(['.', '/']) {
+		return Err(syn::Error::new(lit.span(), "the class name can't be qualified, put its package in 'package' (and write nested classes as Outer$Inner)"));
+	}
+	java_identifier(lit)
+}
+
+/// Parses a package name, either dotted or slashed, into its slashed form. May be empty.
+fn java_package(lit: &LitStr) -> Result<String, syn::Error> {
+	if lit.value().is_empty() {
+		return Ok(String::new());
+	}
+	java_class(lit)
+}
+
+/// Parses a fully qualified class name, either dotted or slashed, into its slashed form.
+pub(crate) fn java_class(lit: &LitStr) -> Result<String, syn::Error> {
+	let name = lit.value();
+	if let Some(part) = name.split(['.', '/']).find(|part| !is_java_identifier(part)) {
+		return Err(syn::Error::new(lit.span(), format!("'{name}' is not a valid Java class name: '{part}' is not a valid identifier")));
+	}
+	Ok(name.replace('.', "/"))
 }
@@ -0,0 +1,126 @@
+use proc_macro2::TokenStream;
+use syn::{ImplItem, Item};
+
+use crate::{args::strip_param_attrs, attrs::AttrsOptions, wrapper::{generate_fn_wrapper, mark_inline, Wrapper}};
+
+/// Removes any `#[jni]` attribute from the given list, parsing its options.
+fn take_jni_attr(attrs: &mut Vec<syn::Attribute>) -> Result<Option<AttrsOptions>, syn::Error> {
+	let Some(pos) = attrs.iter().position(|a| a.path().segments.last().is_some_and(|s| s.ident == "jni")) else {
+		return Ok(None);
+	};
+	let attr = attrs.remove(pos);
+	match attr.meta {
+		syn::Meta::Path(_) => Ok(Some(AttrsOptions::default())),
+		syn::Meta::List(list) => Ok(Some(AttrsOptions::parse_attr(list.tokens)?)),
+		syn::Meta::NameValue(meta) => Err(syn::Error::new_spanned(meta, "expected #[jni] or #[jni(...)]")),
+	}
+}
+
+/// Decides whether an item inside a `#[jni]` block should be exported, and with which options.
+/// Public items are exported by default, others only when explicitly marked.
+fn item_options(item_attrs: &mut Vec<syn::Attribute>, public: bool, defaults: &AttrsOptions) -> Result<Option<AttrsOptions>, syn::Error> {
+	match take_jni_attr(item_attrs)? {
+		Some(opts) if opts.skip => Ok(None),
+		Some(opts) => Ok(Some(opts.inherit(defaults))),
+		None if public => Ok(Some(AttrsOptions::default().inherit(defaults))),
+		None => Ok(None),
+	}
+}
+
+fn is_public(vis: &syn::Visibility) -> bool {
+	matches!(vis, syn::Visibility::Public(_))
+}
+
+/// Forces long-form symbols on every exported function sharing its Java name with another one.
+fn mark_overloads(exported: &mut [(usize, String, AttrsOptions)]) {
+	let key = |(_, ident, opts): &(usize, String, AttrsOptions)| {
+		(opts.package.clone(), opts.class.clone(), opts.name.clone().unwrap_or_else(|| ident.clone()))
+	};
+	let keys: Vec<_> = exported.iter().map(key).collect();
+	for (n, (_, _, opts)) in exported.iter_mut().enumerate() {
+		if keys.iter().filter(|k| **k == keys[n]).count() > 1 {
+			opts.overload = true;
+		}
+	}
+}
+
+/// Exports every public associated function of an `impl` block, placing wrappers inside the block itself.
+/// Returns the registrations for `RegisterNatives`, which must be placed outside of the block.
+pub(crate) fn expand_impl(impl_item: &mut syn::ItemImpl, defaults: &AttrsOptions) -> Result<TokenStream, syn::Error> {
+	defaults.reject_item_only()?;
+	if let Some((_, path, _)) = &impl_item.trait_ {
+		// wrappers can't be added to a trait implementation, as they aren't part of the trait
+		return Err(syn::Error::new_spanned(path, "#[jni] can't be used on trait implementations, use an inherent impl block"));
+	}
+	let generics = &impl_item.generics;
+	if generics.type_params().next().is_some() || generics.const_params().next().is_some() {
+		// the wrappers would be generic too, and never get a symbol
+		return Err(syn::Error::new_spanned(generics, "#[jni] can't be used on generic impl blocks"));
+	}
+	let mut exported = Vec::new();
+	for (n, item) in impl_item.items.iter_mut().enumerate() {
+		let ImplItem::Fn(fn_item) = item else { continue };
+		let Some(opts) = item_options(&mut fn_item.attrs, is_public(&fn_item.vis), defaults)? else { continue };
+		exported.push((n, fn_item.sig.ident.to_string(), opts));
+	}
+	mark_overloads(&mut exported);
+
+	let mut wrappers = Vec::new();
+	let mut registrations = TokenStream::new();
+	for (n, _, opts) in exported {
+		let ImplItem::Fn(fn_item) = &mut impl_item.items[n] else { continue };
+		let as_fn = syn::ItemFn {
+			attrs: Vec::new(),
+			vis: fn_item.vis.clone(),
+			sig: fn_item.sig.clone(),
+			block: Box::new(fn_item.block.clone()),
+		};
+		let Wrapper { function, registration } = generate_fn_wrapper(&as_fn, &opts, Some(&impl_item.self_ty))?;
+		wrappers.push(ImplItem::Verbatim(function));
+		registrations.extend(registration);
+		mark_inline(&mut fn_item.attrs, &opts);
+		strip_param_attrs(&mut fn_item.sig);
+	}
+	impl_item.items.extend(wrappers);
+	Ok(registrations)
+}
+
+/// Exports every public function of an inline module, placing wrappers inside the module itself.
+/// Nested `impl` blocks and modules are only expanded when marked with `#[jni]`, inheriting these options.
+pub(crate) fn expand_mod(mod_item: &mut syn::ItemMod, defaults: &AttrsOptions) -> Result<(), syn::Error> {
+	defaults.reject_item_only()?;
+	let Some((_, ref mut items)) = mod_item.content else {
+		return Err(syn::Error::new_spanned(&mod_item.ident, "#[jni] can only be used on inline modules"));
+	};
+	let mut exported = Vec::new();
+	let mut wrappers = Vec::new();
+	for (n, item) in items.iter_mut().enumerate() {
+		match item {
+			Item::Fn(fn_item) => {
+				let Some(opts) = item_options(&mut fn_item.attrs, is_public(&fn_item.vis), defaults)? else { continue };
+				exported.push((n, fn_item.sig.ident.to_string(), opts));
+			},
+			Item::Impl(impl_item) => {
+				let Some(opts) = item_options(&mut impl_item.attrs, false, defaults)? else { continue };
+				let registrations = expand_impl(impl_item, &opts)?;
+				wrappers.push(Item::Verbatim(registrations));
+			},
+			Item::Mod(inner) => {
+				let Some(opts) = item_options(&mut inner.attrs, false, defaults)? else { continue };
+				expand_mod(inner, &opts)?;
+			},
+			_ => continue,
+		}
+	}
+	mark_overloads(&mut exported);
+
+	for (n, _, opts) in exported {
+		let Item::Fn(fn_item) = &mut items[n] else { continue };
+		let Wrapper { function, registration } = generate_fn_wrapper(fn_item, &opts, None)?;
+		wrappers.push(Item::Verbatim(quote::quote!( #function #registration )));
+		mark_inline(&mut fn_item.attrs, &opts);
+		strip_param_attrs(&mut fn_item.sig);
+	}
+	items.extend(wrappers);
+	Ok(())
+}
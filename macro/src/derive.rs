@@ -0,0 +1,242 @@
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::{spanned::Spanned, Data, DeriveInput, Fields};
+
+use crate::attrs::java_class;
+
+/// Which exception a type or variant is thrown as.
+enum Target {
+	Class(String),
+	Transparent,
+}
+
+/// Parses the `#[jni_error(...)]` attribute, if present.
+fn parse_target(attrs: &[syn::Attribute]) -> Result<Option<Target>, syn::Error> {
+	let mut target = None;
+	for attr in attrs.iter().filter(|a| a.path().is_ident("jni_error")) {
+		attr.parse_nested_meta(|meta| {
+			if target.is_some() {
+				return Err(meta.error("exception class already specified"));
+			}
+			if meta.path.is_ident("class") {
+				target = Some(Target::Class(java_class(&meta.value()?.parse()?)?));
+				Ok(())
+			} else if meta.path.is_ident("transparent") {
+				target = Some(Target::Transparent);
+				Ok(())
+			} else {
+				Err(meta.error("expected `class = \"...\"` or `transparent`"))
+			}
+		})?;
+	}
+	Ok(target)
+}
+
+/// A single case of the generated `match`.
+struct Arm {
+	pattern: TokenStream,
+	/// Set when delegating to the wrapped error, bound as `inner` by the pattern.
+	transparent: bool,
+	class: String,
+	/// Binds the field holding a `std::backtrace::Backtrace` as `backtrace`, if there is one.
+	backtrace: Option<TokenStream>,
+}
+
+/// Finds the field holding the backtrace of where the error happened, recognized by its type.
+fn backtrace_field(fields: &Fields) -> Option<TokenStream> {
+	fields.iter().enumerate()
+		.find(|(_, f)| matches!(&f.ty, syn::Type::Path(p) if p.path.segments.last().is_some_and(|s| s.ident == "Backtrace")))
+		.map(|(n, f)| match &f.ident {
+			Some(name) => quote::quote!( #name ),
+			None => syn::Index::from(n).into_token_stream(),
+		})
+}
+
+fn arm(path: TokenStream, fields: &Fields, target: Target, span: Span) -> Result<Arm, syn::Error> {
+	match target {
+		Target::Class(class) => {
+			let backtrace = backtrace_field(fields).map(|field| quote::quote!( #path { #field: backtrace, .. } ));
+			Ok(Arm { pattern: quote::quote!( #path { .. } ), transparent: false, class, backtrace })
+		},
+		Target::Transparent => {
+			if fields.len() != 1 {
+				return Err(syn::Error::new(span, "#[jni_error(transparent)] requires exactly one field"));
+			}
+			let pattern = match fields {
+				Fields::Named(named) => {
+					let name = &named.named[0].ident;
+					quote::quote!( #path { #name: inner } )
+				},
+				_ => quote::quote!( #path(inner) ),
+			};
+			Ok(Arm { pattern, transparent: true, class: String::new(), backtrace: None })
+		},
+	}
+}
+
+pub(crate) fn derive_jni_toolbox_error(input: DeriveInput) -> Result<TokenStream, syn::Error> {
+	let default = parse_target(&input.attrs)?;
+	let arms = match &input.data {
+		Data::Struct(data) => {
+			let Some(target) = default else {
+				return Err(syn::Error::new(input.ident.span(), "missing #[jni_error(class = \"...\")] or #[jni_error(transparent)]"));
+			};
+			vec![arm(quote::quote!(Self), &data.fields, target, input.ident.span())?]
+		},
+		Data::Enum(data) => {
+			let default_class = match default {
+				None => None,
+				Some(Target::Class(class)) => Some(class),
+				Some(Target::Transparent) => return Err(syn::Error::new(input.ident.span(), "#[jni_error(transparent)] can only be used on variants")),
+			};
+			let mut arms = Vec::new();
+			for variant in &data.variants {
+				let java_exception = variant.attrs.iter().any(|a| a.path().is_ident("java_exception"));
+				let target = match (parse_target(&variant.attrs)?, &default_class) {
+					(Some(target), _) => target,
+					// captured Java exceptions are rethrown as they are
+					(None, _) if java_exception => Target::Transparent,
+					(None, Some(class)) => Target::Class(class.clone()),
+					(None, None) => return Err(syn::Error::new(variant.span(), "missing exception class for this variant: add #[jni_error(class = \"...\")] to it or to the enum")),
+				};
+				let ident = &variant.ident;
+				arms.push(arm(quote::quote!(Self::#ident), &variant.fields, target, variant.span())?);
+			}
+			arms
+		},
+		Data::Union(_) => return Err(syn::Error::new(input.ident.span(), "JniToolboxError can't be derived for unions")),
+	};
+
+	let name = &input.ident;
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+	let jclass = arms.iter().map(|Arm { pattern, transparent, class, .. }| if *transparent {
+		quote::quote!( #pattern => ::jni_toolbox::JniToolboxError::jclass(inner), )
+	} else {
+		quote::quote!( #pattern => #class, )
+	});
+
+	// message and constructor only need to be overridden when delegating
+	let delegated = if arms.iter().any(|a| a.transparent) {
+		let message = arms.iter().map(|Arm { pattern, transparent, .. }| if *transparent {
+			quote::quote!( #pattern => ::jni_toolbox::JniToolboxError::message(inner), )
+		} else {
+			quote::quote!( #pattern => self.to_string(), )
+		});
+		let to_throwable = arms.iter().map(|Arm { pattern, transparent, .. }| if *transparent {
+			quote::quote!( #pattern => ::jni_toolbox::JniToolboxError::to_throwable(inner, env), )
+		} else {
+			quote::quote!( #pattern => ::jni_toolbox::error::new_throwable(env, ::jni_toolbox::JniToolboxError::jclass(self), &::jni_toolbox::JniToolboxError::message(self)), )
+		});
+		let attach_causes = arms.iter().map(|Arm { pattern, transparent, .. }| if *transparent {
+			quote::quote!( #pattern => ::jni_toolbox::JniToolboxError::attach_causes(inner), )
+		} else {
+			quote::quote!( #pattern => true, )
+		});
+		quote::quote! {
+			fn attach_causes(&self) -> bool {
+				match self {
+					#(#attach_causes)*
+				}
+			}
+
+			fn message(&self) -> String {
+				match self {
+					#(#message)*
+				}
+			}
+
+			fn to_throwable<'j>(&self, env: &mut ::jni_toolbox::jni::JNIEnv<'j>) -> Result<::jni_toolbox::jni::objects::JThrowable<'j>, ::jni_toolbox::jni::errors::Error> {
+				match self {
+					#(#to_throwable)*
+				}
+			}
+		}
+	} else {
+		TokenStream::new()
+	};
+
+	// wrapped errors provide their own backtrace, others the one in their `Backtrace` field
+	let backtrace = if arms.iter().any(|a| a.transparent || a.backtrace.is_some()) {
+		let backtrace = arms.iter().map(|Arm { pattern, transparent, backtrace, .. }| match backtrace {
+			_ if *transparent => quote::quote!( #pattern => ::jni_toolbox::JniToolboxError::backtrace(inner), ),
+			Some(bound) => quote::quote!( #bound => Some(backtrace), ),
+			None => quote::quote!( #pattern => None, ),
+		});
+		quote::quote! {
+			fn backtrace(&self) -> Option<&::std::backtrace::Backtrace> {
+				match self {
+					#(#backtrace)*
+				}
+			}
+		}
+	} else {
+		TokenStream::new()
+	};
+
+	Ok(quote::quote! {
+		impl #impl_generics ::jni_toolbox::JniToolboxError for #name #ty_generics #where_clause {
+			fn jclass(&self) -> &str {
+				match self {
+					#(#jclass)*
+				}
+			}
+
+			#delegated
+
+			#backtrace
+		}
+	})
+}
+
+/// Builds a variant holding a single value, bound as `e`.
+fn construct(variant: &syn::Variant) -> Result<TokenStream, syn::Error> {
+	let ident = &variant.ident;
+	match &variant.fields {
+		Fields::Named(named) if named.named.len() == 1 => {
+			let name = &named.named[0].ident;
+			Ok(quote::quote!( Self::#ident { #name: e } ))
+		},
+		Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => Ok(quote::quote!( Self::#ident(e) )),
+		_ => Err(syn::Error::new(variant.span(), "#[java_exception] variants must hold exactly one JavaException")),
+	}
+}
+
+pub(crate) fn derive_from_java_exception(input: DeriveInput) -> Result<TokenStream, syn::Error> {
+	let Data::Enum(data) = &input.data else {
+		return Err(syn::Error::new(input.ident.span(), "FromJavaException can only be derived for enums"));
+	};
+
+	let mut checks = Vec::new();
+	let mut fallback = None;
+	for variant in &data.variants {
+		let Some(attr) = variant.attrs.iter().find(|a| a.path().is_ident("java_exception")) else { continue };
+		let construct = construct(variant)?;
+		match &attr.meta {
+			syn::Meta::Path(_) if fallback.is_some() => return Err(syn::Error::new(variant.span(), "only one catch-all #[java_exception] variant is allowed")),
+			syn::Meta::Path(_) => fallback = Some(construct),
+			_ => {
+				let class = java_class(&attr.parse_args()?)?;
+				checks.push(quote::quote! {
+					if e.is_instance_of(#class) {
+						return #construct;
+					}
+				});
+			},
+		}
+	}
+	let Some(fallback) = fallback else {
+		return Err(syn::Error::new(input.ident.span(), "missing a catch-all #[java_exception] variant, for exceptions matching no other class"));
+	};
+
+	let name = &input.ident;
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+	Ok(quote::quote! {
+		impl #impl_generics From<::jni_toolbox::JavaException> for #name #ty_generics #where_clause {
+			fn from(e: ::jni_toolbox::JavaException) -> Self {
+				#(#checks)*
+				#fallback
+			}
+		}
+	})
+}
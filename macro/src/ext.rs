@@ -16,6 +16,6 @@ pub(crate) fn bare_type(ty: Box<syn::Type>) -> Option<syn::TypePath> {
 		syn::Type::Paren(p) => bare_type(p.elem),
 		syn::Type::Reference(r) => bare_type(r.elem),
 		syn::Type::Path(ty) => Some(ty),
-		_ => todo!(),
+		_ => None,
 	}
 }
@@ -0,0 +1,132 @@
+use proc_macro2::TokenStream;
+use syn::visit_mut::VisitMut;
+
+use crate::{attrs::AttrsOptions, wrapper::{generate_single_wrapper, Wrapper}};
+
+/// Replaces type parameters with the concrete types of an instantiation.
+struct Substitute<'a> {
+	types: &'a [(syn::Ident, syn::Type)],
+	generics: &'a syn::Generics,
+	errors: Vec<syn::Error>,
+}
+
+impl Substitute<'_> {
+	/// The only trait bound of a type parameter, used to qualify its associated types.
+	fn single_bound(&self, param: &syn::Ident) -> Option<syn::Path> {
+		let declared = self.generics.type_params()
+			.filter(|p| p.ident == *param)
+			.flat_map(|p| p.bounds.iter());
+		let predicated = self.generics.where_clause.iter()
+			.flat_map(|w| w.predicates.iter())
+			.filter_map(|p| match p {
+				syn::WherePredicate::Type(p) if matches!(&p.bounded_ty, syn::Type::Path(t) if t.qself.is_none() && t.path.is_ident(param)) => Some(p.bounds.iter()),
+				_ => None,
+			})
+			.flatten();
+		let mut traits = declared.chain(predicated).filter_map(|b| match b {
+			syn::TypeParamBound::Trait(t) if matches!(t.modifier, syn::TraitBoundModifier::None) => Some(&t.path),
+			_ => None,
+		});
+		let mut bound = traits.next()?.clone();
+		if traits.next().is_some() {
+			return None;
+		}
+		// constraints such as `Item: Default` are only allowed in bounds
+		if let Some(last) = bound.segments.last_mut() {
+			if let syn::PathArguments::AngleBracketed(args) = &mut last.arguments {
+				args.args = std::mem::take(&mut args.args).into_iter()
+					.filter(|a| matches!(a, syn::GenericArgument::Lifetime(_) | syn::GenericArgument::Type(_) | syn::GenericArgument::Const(_)))
+					.collect();
+				if args.args.is_empty() {
+					last.arguments = syn::PathArguments::None;
+				}
+			}
+		}
+		Some(bound)
+	}
+}
+
+impl VisitMut for Substitute<'_> {
+	fn visit_type_mut(&mut self, ty: &mut syn::Type) {
+		if let syn::Type::Path(path) = ty {
+			let first = path.path.segments.first().filter(|_| path.qself.is_none() && path.path.leading_colon.is_none());
+			if let Some((param, concrete)) = first.and_then(|s| self.types.iter().find(|(name, _)| *name == s.ident)) {
+				if path.path.segments.len() == 1 {
+					*ty = concrete.clone();
+					return;
+				}
+				// `T::Item` becomes `<Concrete as Bound>::Item`, which needs to know the trait
+				let Some(mut bound) = self.single_bound(param) else {
+					self.errors.push(syn::Error::new_spanned(&*path, format!("can't tell which trait this belongs to, write it as <{param} as Trait>::...")));
+					return;
+				};
+				self.visit_path_mut(&mut bound);
+				let position = bound.segments.len();
+				let rest = path.path.segments.iter().skip(1).cloned();
+				bound.segments.extend(rest);
+				*ty = syn::Type::Path(syn::TypePath {
+					qself: Some(syn::QSelf {
+						lt_token: Default::default(),
+						ty: Box::new(concrete.clone()),
+						position,
+						as_token: Some(Default::default()),
+						gt_token: Default::default(),
+					}),
+					path: bound,
+				});
+				return;
+			}
+		}
+		// `<T as Trait>::X` is qualified already, and only needs `T` replaced
+		syn::visit_mut::visit_type_mut(self, ty);
+	}
+}
+
+/// Generates one JNI function for each `instantiate(...)` of a generic function. Instantiations
+/// sharing their Java name are exported as overloads.
+pub(crate) fn expand_instantiations(fn_item: &syn::ItemFn, attrs: &AttrsOptions, self_ty: Option<&syn::Type>) -> Result<Wrapper, syn::Error> {
+	let generics = &fn_item.sig.generics;
+	if let Some(param) = generics.const_params().next() {
+		return Err(syn::Error::new_spanned(param, "const generics can't be instantiated"));
+	}
+	if attrs.instantiations.is_empty() {
+		return Err(syn::Error::new_spanned(generics, "generic functions must list their exported versions, as in instantiate(T = i32, name = \"...\")"));
+	}
+	let params: Vec<_> = generics.type_params().map(|p| &p.ident).collect();
+	let fn_name = fn_item.sig.ident.to_string();
+	let names: Vec<_> = attrs.instantiations.iter()
+		.map(|i| i.name.clone().or_else(|| attrs.name.clone()).unwrap_or_else(|| fn_name.clone()))
+		.collect();
+
+	let mut function = TokenStream::new();
+	let mut registration = TokenStream::new();
+	for (instantiation, name) in attrs.instantiations.iter().zip(&names) {
+		if let Some((param, _)) = instantiation.types.iter().find(|(p, _)| !params.contains(&p)) {
+			return Err(syn::Error::new(param.span(), format!("'{param}' is not a type parameter of this function")));
+		}
+		let types = params.iter()
+			.map(|param| instantiation.types.iter()
+				.find(|(p, _)| p == *param)
+				.map(|(_, ty)| ty)
+				.ok_or_else(|| syn::Error::new(instantiation.span, format!("missing a type for '{param}'")))
+			)
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let mut sig = fn_item.sig.clone();
+		let mut substitute = Substitute { types: &instantiation.types, generics, errors: Vec::new() };
+		substitute.visit_signature_mut(&mut sig);
+		let mut errors = substitute.errors.into_iter();
+		if let Some(mut error) = errors.next() {
+			errors.for_each(|other| error.combine(other));
+			return Err(error);
+		}
+		let concrete = syn::ItemFn { attrs: Vec::new(), vis: fn_item.vis.clone(), sig, block: fn_item.block.clone() };
+		let mut opts = attrs.clone();
+		opts.name = Some(name.clone());
+		opts.overload |= names.iter().filter(|n| *n == name).count() > 1;
+		let wrapper = generate_single_wrapper(&concrete, &opts, self_ty, quote::quote!( ::<#(#types),*> ))?;
+		function.extend(wrapper.function);
+		registration.extend(wrapper.registration);
+	}
+	Ok(Wrapper { function, registration })
+}
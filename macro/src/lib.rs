@@ -3,6 +3,13 @@ mod wrapper;
 mod args;
 mod ret;
 mod ext;
+mod block;
+mod sig;
+mod derive;
+mod generics;
+mod manifest;
+
+use jni_toolbox_mangle as mangle;
 
 /// Wrap this function in in a JNI exported fn.
 #[proc_macro_attribute]
@@ -14,6 +21,22 @@ pub fn jni(
 		syn::parse_macro_input!(attrs),
 		syn::parse_macro_input!(input),
 	)
-		.unwrap()
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
+
+/// Implement `JniToolboxError`, picking the exception class from `#[jni_error(...)]` attributes.
+#[proc_macro_derive(JniToolboxError, attributes(jni_error))]
+pub fn derive_jni_toolbox_error(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	derive::derive_jni_toolbox_error(syn::parse_macro_input!(input))
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
+
+/// Implement `From<JavaException>`, picking the variant from `#[java_exception(...)]` attributes.
+#[proc_macro_derive(FromJavaException, attributes(java_exception))]
+pub fn derive_from_java_exception(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	derive::derive_from_java_exception(syn::parse_macro_input!(input))
+		.unwrap_or_else(syn::Error::into_compile_error)
 		.into()
 }
@@ -0,0 +1,35 @@
+use std::path::Path;
+
+use proc_macro2::{Span, TokenStream};
+
+/// The path of the manifest of the crate being compiled, if cargo is compiling one.
+fn manifest_path() -> Option<String> {
+	let dir = std::env::var("CARGO_MANIFEST_DIR").ok()?;
+	Some(Path::new(&dir).join("Cargo.toml").to_string_lossy().into_owned())
+}
+
+/// Reads the crate-wide `register` switch from the `[package.metadata.jni-toolbox]` table of the
+/// manifest, so that a whole crate can pick registration without repeating it on every item.
+pub(crate) fn crate_register() -> Result<Option<bool>, syn::Error> {
+	let Some(path) = manifest_path() else { return Ok(None) };
+	let Ok(manifest) = std::fs::read_to_string(&path) else { return Ok(None) };
+	let table: toml::Table = manifest.parse()
+		.map_err(|e| syn::Error::new(Span::call_site(), format!("failed parsing {path}: {e}")))?;
+	let register = table.get("package")
+		.and_then(|p| p.get("metadata"))
+		.and_then(|m| m.get("jni-toolbox"))
+		.and_then(|t| t.get("register"));
+	match register {
+		None => Ok(None),
+		Some(toml::Value::Boolean(register)) => Ok(Some(*register)),
+		Some(_) => Err(syn::Error::new(Span::call_site(), format!("'register' in [package.metadata.jni-toolbox] of {path} must be a boolean"))),
+	}
+}
+
+/// Makes cargo rebuild the crate when its manifest changes, as it doesn't for metadata alone.
+pub(crate) fn track() -> TokenStream {
+	match manifest_path() {
+		Some(path) => quote::quote!( const _: &[u8] = ::core::include_bytes!(#path); ),
+		None => TokenStream::new(),
+	}
+}
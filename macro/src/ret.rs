@@ -1,5 +1,6 @@
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::TokenStream;
 use quote::ToTokens;
+use syn::spanned::Spanned;
 use syn::{ReturnType, Type};
 
 use crate::ext::bare_type;
@@ -8,44 +9,38 @@ use crate::ext::bare_type;
 pub(crate) struct ReturnOptions {
 	pub(crate) ty: Option<Box<Type>>,
 	pub(crate) result: bool,
-	pub(crate) pointer: bool,
 	pub(crate) void: bool,
 }
 
-const PRIMITIVE_TYPES: [&str; 7] = ["i8", "i16", "i32", "i64", "f32", "f64", "bool"];
-
 impl ReturnOptions {
 	pub(crate) fn parse_signature(ret: &ReturnType) -> Result<Self, syn::Error> {
 		match ret {
-			syn::ReturnType::Default => Ok(Self { ty: None, result: false, void: true, pointer: false }),
+			syn::ReturnType::Default => Ok(Self { ty: None, result: false, void: true }),
 			syn::ReturnType::Type(_tok, ty) => match bare_type(ty.clone()) {
 				Some(path) => {
 					let Some(last) = path.path.segments.last() else {
-						return Err(syn::Error::new(Span::call_site(), "empty Result type is not valid"));
+						return Err(syn::Error::new_spanned(ty, "empty return type is not valid"));
 					};
 
 					if last.ident == "Result" {
 						match &last.arguments {
-							syn::PathArguments::None => return Err(syn::Error::new(Span::call_site(), "Result without generics is not valid")),
-							syn::PathArguments::Parenthesized(_) => return Err(syn::Error::new(Span::call_site(), "Parenthesized Result is not valid")),
+							syn::PathArguments::None => return Err(syn::Error::new_spanned(last, "Result without generics is not valid")),
+							syn::PathArguments::Parenthesized(args) => return Err(syn::Error::new_spanned(args, "Parenthesized Result is not valid")),
 							syn::PathArguments::AngleBracketed(ref generics) => for generic in generics.args.iter() {
 								match generic {
 									syn::GenericArgument::Lifetime(_) => continue,
 									syn::GenericArgument::Type(ty) => {
-										// TODO checking by making ty a token stream and then string equals is not exactly great!
-										let pointer = !PRIMITIVE_TYPES.iter().any(|t| ty.to_token_stream().to_string() == *t);
-										return Ok(Self { ty: Some(Box::new(ty.clone())), result: true, void: is_void(ty), pointer });
+										return Ok(Self { ty: Some(Box::new(ty.clone())), result: true, void: is_void(ty) });
 									},
-									_ => return Err(syn::Error::new(Span::call_site(), "unexpected type in Result"))
+									_ => return Err(syn::Error::new_spanned(generic, "unexpected type in Result"))
 								}
 							}
 						}
 					}
 
-					let pointer = !PRIMITIVE_TYPES.iter().any(|t| last.ident == t);
-					Ok(Self { ty: Some(Box::new(Type::Path(path.clone()))), result: false, void: false, pointer })
+					Ok(Self { ty: Some(Box::new(Type::Path(path.clone()))), result: false, void: false })
 				},
-				None => Err(syn::Error::new(Span::call_site(), "unsupported return type")),
+				None => Err(syn::Error::new_spanned(ty, "unsupported return type")),
 			},
 		}
 	}
@@ -53,7 +48,8 @@ impl ReturnOptions {
 	pub(crate) fn tokens(&self) -> TokenStream {
 		match &self.ty { // TODO why do we need to invoke syn::Token! macro ???
 			None => ReturnType::Default.to_token_stream(),
-			Some(t) => quote::quote!( -> <#t as jni_toolbox::IntoJava<'local>>::Ret )
+			// spanned on the type, so that a missing conversion is reported on it
+			Some(t) => quote::quote_spanned!(t.span()=> -> <#t as jni_toolbox::IntoJava<'local>>::Ret )
 		}
 	}
 }
@@ -75,6 +71,6 @@ fn is_void(ty: &syn::Type) -> bool {
 		Type::TraitObject(_) => false,
 		Type::Tuple(x) => x.elems.is_empty(),
 		Type::Verbatim(_) => false,
-		_ => todo!(),
+		_ => false,
 	}
 }
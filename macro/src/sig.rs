@@ -0,0 +1,97 @@
+use syn::{GenericArgument, PathArguments, Type};
+
+/// Builds the arguments part of a method descriptor, such as `ILjava/lang/String;`.
+pub(crate) fn arguments_signature(types: &[Type]) -> Result<String, syn::Error> {
+	let mut out = String::new();
+	for ty in types {
+		let Some(sig) = type_signature(ty) else {
+			return Err(syn::Error::new_spanned(ty, "can't infer the Java signature of an argument, specify it with the 'signature' attribute"));
+		};
+		out.push_str(&sig);
+	}
+	Ok(out)
+}
+
+/// Builds a full method descriptor, such as `(ILjava/lang/String;)V`.
+pub(crate) fn method_signature(types: &[Type], ret: Option<&Type>) -> Result<String, syn::Error> {
+	let ret = match ret {
+		None => "V".to_string(),
+		Some(ty) => type_signature(ty).ok_or_else(|| syn::Error::new_spanned(ty, "can't infer the Java signature of the return type, specify it with the 'signature' attribute"))?,
+	};
+	Ok(format!("({}){ret}", arguments_signature(types)?))
+}
+
+/// Infers the JNI type signature of a Rust type, as seen from Java.
+///
+/// This only works for types known to the toolbox, as the macro can't resolve traits: other types
+/// need an explicit `signature` attribute. So do untyped objects such as `JObject`, which may stand
+/// for any Java class, rather than guessing `java.lang.Object`.
+pub(crate) fn type_signature(ty: &Type) -> Option<String> {
+	match ty {
+		Type::Reference(r) => type_signature(&r.elem),
+		Type::Group(g) => type_signature(&g.elem),
+		Type::Paren(p) => type_signature(&p.elem),
+		Type::Tuple(t) if t.elems.is_empty() => Some("V".to_string()),
+		Type::Path(p) => {
+			let last = p.path.segments.last()?;
+			let sig = match last.ident.to_string().as_str() {
+				"i8" | "jbyte" => "B",
+				"i16" | "jshort" => "S",
+				"i32" | "jint" | "jsize" => "I",
+				"i64" | "jlong" => "J",
+				"f32" | "jfloat" => "F",
+				"f64" | "jdouble" => "D",
+				"bool" | "jboolean" => "Z",
+				"char" | "jchar" => "C",
+				"String" | "str" | "JString" | "jstring" => "Ljava/lang/String;",
+				"JClass" | "jclass" => "Ljava/lang/Class;",
+				"Uuid" => "Ljava/util/UUID;",
+				"JByteArray" | "jbyteArray" => "[B",
+				"JShortArray" | "jshortArray" => "[S",
+				"JIntArray" | "jintArray" => "[I",
+				"JLongArray" | "jlongArray" => "[J",
+				"JFloatArray" | "jfloatArray" => "[F",
+				"JDoubleArray" | "jdoubleArray" => "[D",
+				"JBooleanArray" | "jbooleanArray" => "[Z",
+				"JCharArray" | "jcharArray" => "[C",
+				"Option" => return type_signature(first_generic(&last.arguments)?),
+				"Vec" => return Some(format!("[{}", type_signature(first_generic(&last.arguments)?)?)),
+				_ => return None,
+			};
+			Some(sig.to_string())
+		},
+		_ => None,
+	}
+}
+
+/// The Java name of a Rust type, such as `java.lang.String` or `int[]`, as used in messages.
+pub(crate) fn java_type_name(ty: &Type) -> Option<String> {
+	type_signature(ty).map(|sig| descriptor_name(&sig))
+}
+
+fn descriptor_name(sig: &str) -> String {
+	if let Some(inner) = sig.strip_prefix('[') {
+		return format!("{}[]", descriptor_name(inner));
+	}
+	let name = match sig {
+		"B" => "byte",
+		"S" => "short",
+		"I" => "int",
+		"J" => "long",
+		"F" => "float",
+		"D" => "double",
+		"Z" => "boolean",
+		"C" => "char",
+		"V" => "void",
+		_ => return sig.trim_start_matches('L').trim_end_matches(';').replace('/', "."),
+	};
+	name.to_string()
+}
+
+fn first_generic(args: &PathArguments) -> Option<&Type> {
+	let PathArguments::AngleBracketed(args) = args else { return None };
+	args.args.iter().find_map(|arg| match arg {
+		GenericArgument::Type(ty) => Some(ty),
+		_ => None,
+	})
+}
@@ -1,37 +1,179 @@
 use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::spanned::Spanned;
 use syn::Item;
 
-use crate::{args::ArgumentOptions, attrs::AttrsOptions, ret::ReturnOptions};
+use crate::{args::{strip_param_attrs, ArgumentOptions}, attrs::{AttrsOptions, ErrorMapping, MessageFormat, PanicPolicy}, block, generics, mangle, manifest, ret::ReturnOptions, sig};
 
 pub(crate) fn generate_jni_wrapper(attrs: TokenStream, input: TokenStream) -> Result<TokenStream, syn::Error> {
-	let Item::Fn(fn_item) = syn::parse2(input.clone())? else {
-		return Err(syn::Error::new(Span::call_site(), "#[jni] is only supported on functions"));
-	};
+	let mut attrs = AttrsOptions::parse_attr(attrs)?;
+	attrs.register = attrs.register.or(manifest::crate_register()?);
+	let track = manifest::track();
+	match syn::parse2(input)? {
+		_ if attrs.skip => Err(syn::Error::new(attrs.span("skip"), "'skip' can only be used on items inside a #[jni] impl block or module")),
+		Item::Fn(mut fn_item) => {
+			let Wrapper { function, registration } = generate_fn_wrapper(&fn_item, &attrs, None)?;
+			mark_inline(&mut fn_item.attrs, &attrs);
+			strip_param_attrs(&mut fn_item.sig);
+			// the function could be inside an impl block, where unnamed constants aren't allowed
+			fn_item.block.stmts.insert(0, syn::Stmt::Item(Item::Verbatim(track)));
+			Ok(quote::quote! {
+				#fn_item
+
+				#function
+
+				#registration
+			})
+		},
+		Item::Impl(mut impl_item) => {
+			let registrations = block::expand_impl(&mut impl_item, &attrs)?;
+			Ok(quote::quote! {
+				#impl_item
+
+				#registrations
+
+				#track
+			})
+		},
+		Item::Mod(mut mod_item) => {
+			block::expand_mod(&mut mod_item, &attrs)?;
+			Ok(quote::quote!( #mod_item #track ))
+		},
+		item => Err(syn::Error::new_spanned(item, "#[jni] is only supported on functions, impl blocks and inline modules")),
+	}
+}
+
+pub(crate) fn mark_inline(fn_attrs: &mut Vec<syn::Attribute>, attrs: &AttrsOptions) {
+	if attrs.inline {
+		fn_attrs.push(syn::parse_quote!(#[inline]));
+	}
+}
 
-	let attrs = AttrsOptions::parse_attr(attrs)?;
+pub(crate) struct Wrapper {
+	/// The `extern "system"` function, to be placed next to the wrapped one.
+	pub(crate) function: TokenStream,
+	/// In registration mode, the entry collected for `RegisterNatives`, to be placed outside any `impl` block.
+	pub(crate) registration: TokenStream,
+}
+
+/// Generates the JNI function for the given function. If `self_ty` is set, the function is invoked
+/// through `Self`, as the wrapper is placed in the same `impl` block.
+pub(crate) fn generate_fn_wrapper(fn_item: &syn::ItemFn, attrs: &AttrsOptions, self_ty: Option<&syn::Type>) -> Result<Wrapper, syn::Error> {
+	attrs.validate()?;
+	if !attrs.targets.is_empty() {
+		// every target gets its own symbol, all calling the same function
+		let mut wrapper = generate_fn_wrapper(fn_item, &AttrsOptions { targets: Vec::new(), ..attrs.clone() }, self_ty)?;
+		for target in &attrs.targets {
+			// like the function's own name, a target's one only applies to instantiations without their own
+			if let (Some(span), true) = (target.name_span, attrs.instantiations.iter().any(|i| i.name.is_some())) {
+				return Err(syn::Error::new(span, "targets can't rename instantiations which have their own name"));
+			}
+			let extra = generate_fn_wrapper(fn_item, &attrs.retarget(target), self_ty)?;
+			wrapper.function.extend(extra.function);
+			wrapper.registration.extend(extra.registration);
+		}
+		return Ok(wrapper);
+	}
+	let generics = &fn_item.sig.generics;
+	if generics.type_params().next().is_some() || generics.const_params().next().is_some() {
+		return generics::expand_instantiations(fn_item, attrs, self_ty);
+	}
+	if let Some(instantiation) = attrs.instantiations.first() {
+		return Err(syn::Error::new(instantiation.span, "only generic functions can be instantiated"));
+	}
+	generate_single_wrapper(fn_item, attrs, self_ty, TokenStream::new())
+}
+
+/// Generates the JNI function for a function without type parameters, or for an instantiation of
+/// a generic one, invoking it with the given turbofish.
+pub(crate) fn generate_single_wrapper(fn_item: &syn::ItemFn, attrs: &AttrsOptions, self_ty: Option<&syn::Type>, turbofish: TokenStream) -> Result<Wrapper, syn::Error> {
 	let ret = ReturnOptions::parse_signature(&fn_item.sig.output)?;
-	let return_expr = if ret.void {
-		quote::quote!( () )
-	} else if ret.pointer {
-		quote::quote!( std::ptr::null_mut() )
-	} else {
-		quote::quote!( 0 )
+	let errors_as_values = attrs.errors_as_values.unwrap_or(false);
+	if errors_as_values && !ret.result {
+		let span = match &fn_item.sig.output {
+			syn::ReturnType::Default => fn_item.sig.ident.span(),
+			syn::ReturnType::Type(_, ty) => ty.span(),
+		};
+		return Err(syn::Error::new(span, "returning errors as values requires a function returning Result"));
+	}
+	if attrs.ret_with.is_some() && (ret.void || ret.ty.is_none()) {
+		return Err(syn::Error::new(fn_item.sig.ident.span(), "'ret_with' requires a function returning a value"));
+	}
+	// returned when throwing, so it's never seen by Java
+	let return_expr = match (&ret.ty, &attrs.ret_with) {
+		_ if errors_as_values => quote::quote!( std::ptr::null_mut() ),
+		(None, _) => quote::quote!( () ),
+		(Some(_), Some(with)) => quote::quote!( <#with::Ret<'local> as jni_toolbox::JniDefault>::jni_default() ),
+		(Some(ty), None) => quote::quote!( <<#ty as jni_toolbox::IntoJava<'local>>::Ret as jni_toolbox::JniDefault>::jni_default() ),
 	};
 
 	// TODO a bit ugly passing the return expr down... we should probably manage returns here
-	let args = ArgumentOptions::parse_args(&fn_item, return_expr.clone())?;
+	let args = ArgumentOptions::parse_args(fn_item, attrs, return_expr.clone())?;
 
-	let return_type = ret.tokens();
+	let return_type = match &attrs.ret_with {
+		_ if errors_as_values => quote::quote!( -> jni::sys::jobject ),
+		Some(with) => quote::quote!( -> #with::Ret<'local> ),
+		None => ret.tokens(),
+	};
 
+	// a standalone #[jni] can't see the impl block, but a signature naming Self must be inside one
+	let associated = self_ty.is_none() && mentions_self(fn_item.sig.to_token_stream());
 	let name = fn_item.sig.ident.to_string();
-	let name_jni = name.replace("_", "_1");
-	let fn_name_inner = syn::Ident::new(&name, Span::call_site());
-	let fn_name = syn::Ident::new(&format!("Java_{}_{}_{name_jni}", attrs.package, attrs.class), Span::call_site());
+	let fn_name_inner = fn_item.sig.ident.clone();
+	let java_name = attrs.name.as_deref().unwrap_or(&name);
+	let symbol = if attrs.overload {
+		let signature = match &attrs.signature {
+			Some(signature) => signature.value(),
+			None => sig::arguments_signature(&args.java_types)?,
+		};
+		mangle::long_name(attrs.package(&fn_name_inner)?, attrs.class(&fn_name_inner)?, java_name, &signature)
+	} else {
+		mangle::short_name(attrs.package(&fn_name_inner)?, attrs.class(&fn_name_inner)?, java_name)
+	};
+	let fn_name = syn::Ident::new(&symbol, Span::call_site());
+
+	let registration = if attrs.register() {
+		let signature = match &attrs.signature {
+			Some(signature) if signature.value().starts_with('(') => signature.value(),
+			Some(signature) => return Err(syn::Error::new(signature.span(), "registering natives requires the full method descriptor as 'signature'")),
+			None if errors_as_values => format!("({})Ljni/toolbox/Result;", sig::arguments_signature(&args.java_types)?),
+			// the module's Java type is unknown here, so the signature can't be inferred
+			None if attrs.ret_with.is_some() => return Err(syn::Error::new(attrs.span("ret_with"), "registering natives using 'ret_with' requires the full method descriptor as 'signature'")),
+			None => sig::method_signature(&args.java_types, ret.ty.as_deref())?,
+		};
+		let class = match attrs.package(&fn_name_inner)? {
+			"" => attrs.class(&fn_name_inner)?.to_string(),
+			package => format!("{}/{}", package.replace('.', "/"), attrs.class(&fn_name_inner)?),
+		};
+		let fn_path = match (self_ty, &args.receiver) {
+			(Some(ty), _) => quote::quote!( <#ty>::#fn_name ),
+			(None, None) if !associated => quote::quote!( #fn_name ),
+			(None, _) => return Err(syn::Error::new_spanned(&fn_item.sig.inputs, "registering methods requires #[jni] on the whole impl block")),
+		};
+		quote::quote! {
+			jni_toolbox::register::inventory::submit! {
+				jni_toolbox::register::NativeMethod {
+					class: #class,
+					name: #java_name,
+					signature: #signature,
+					fn_ptr: #fn_path as *mut std::ffi::c_void,
+				}
+			}
+		}
+	} else {
+		TokenStream::new()
+	};
+
+	let export = if attrs.register() {
+		quote::quote!()
+	} else {
+		quote::quote!(#[no_mangle])
+	};
 
 	let incoming = args.incoming;
 	// V----------------------------------V
 	let header = quote::quote! {
-		#[no_mangle]
+		#export
 		#[allow(unused_unit, non_snake_case)]
 		pub extern "system" fn #fn_name<'local>(#incoming) #return_type
 	};
@@ -39,76 +181,166 @@ pub(crate) fn generate_jni_wrapper(attrs: TokenStream, input: TokenStream) -> Re
 
 	let transforming = args.transforming;
 	let transformations = quote::quote! {
-		use jni_toolbox::{JniToolboxError, FromJava, IntoJava};
+		use jni_toolbox::{FromJava, IntoJava};
 		#transforming
 	};
 
 
 	let env_iden = args.env;
 	let forwarding = args.forwarding;
-	let invocation = quote::quote! {
-		let result = #fn_name_inner(#forwarding);
-	};
-
-
-	let error_handling = if ret.result {
-		if let Some(exception) = attrs.exception {
+	let invocation = match args.receiver {
+		None if self_ty.is_some() || associated => quote::quote! {
+			let result = Self::#fn_name_inner #turbofish(#forwarding);
+		},
+		None => quote::quote! {
+			let result = #fn_name_inner #turbofish(#forwarding);
+		},
+		Some(receiver) => {
+			let handle = receiver.handle;
+			let (with_peer, peer) = if receiver.mutable {
+				(quote::quote!(with_peer_mut), quote::quote!(peer: &mut Self))
+			} else {
+				(quote::quote!(with_peer), quote::quote!(peer: &Self))
+			};
 			quote::quote! {
-				let ret = match result {
+				let result = match jni_toolbox::peer::#with_peer(#handle, |#peer| peer.#fn_name_inner #turbofish(#forwarding)) {
 					Ok(x) => x,
-					Err(e) => match #env_iden.throw_new(#exception, format!("{e:?}")) {
-						Ok(_) => return #return_expr,
-						Err(e) => panic!("error throwing java exception: {e}"),
-					}
+					Err(e) => {
+						jni_toolbox::error::throw_error(&mut #env_iden, &e);
+						return #return_expr;
+					},
 				};
 			}
+		},
+	};
+
+
+	let message_format = match attrs.message {
+		None => quote::quote!( None ),
+		Some(MessageFormat::Display) => quote::quote!( Some(jni_toolbox::error::MessageFormat::Display) ),
+		Some(MessageFormat::Debug) => quote::quote!( Some(jni_toolbox::error::MessageFormat::Debug) ),
+		Some(MessageFormat::Chain) => quote::quote!( Some(jni_toolbox::error::MessageFormat::Chain) ),
+	};
+
+	// the exception attribute doesn't require the error to implement Error, unless it's needed for the message
+	let exception_message = match attrs.message {
+		None | Some(MessageFormat::Debug) => quote::quote!( format!("{e:?}") ),
+		Some(MessageFormat::Display) => quote::quote!( format!("{e}") ),
+		Some(MessageFormat::Chain) => quote::quote!( jni_toolbox::error::MessageFormat::Chain.format(&e) ),
+	};
+
+	// spanned on the return type, so that a missing conversion is reported on it
+	let ret_span = ret.ty.as_ref().map_or_else(Span::call_site, |ty| ty.span());
+	let into_java = |value: TokenStream| match &attrs.ret_with {
+		Some(with) => quote::quote!( #with::into_java(#value, &mut #env_iden) ),
+		None => quote::quote_spanned!(ret_span=> jni_toolbox::IntoJava::into_java(#value, &mut #env_iden)),
+	};
+
+	let mappings: Vec<_> = attrs.errors.iter().map(|ErrorMapping { ty, class }| quote::quote! {
+		(|e: &(dyn std::error::Error + 'static)| e.is::<#ty>(), #class),
+	}).collect();
+
+	let (error_handling, reverse_transformations) = if errors_as_values {
+		let into_java = into_java(quote::quote!(x));
+		let describe = if let Some(exception) = &attrs.exception {
+			quote::quote!( (#exception.to_string(), #exception_message) )
+		} else if !attrs.errors.is_empty() {
+			quote::quote!( jni_toolbox::error::describe_mapped(&e, &[ #(#mappings)* ], #message_format) )
 		} else {
+			quote::quote!( jni_toolbox::error::Throw::describe(&e, #message_format) )
+		};
+		let error_handling = quote::quote! {
+			let ret = match result {
+				// SAFETY: raw objects returned by the conversion are local references of this frame
+				Ok(x) => #into_java.and_then(|fin| unsafe { jni_toolbox::result::ok(&mut #env_iden, fin) }),
+				Err(e) => {
+					let (class, message) = #describe;
+					jni_toolbox::result::err(&mut #env_iden, &class, &message)
+				},
+			};
+		};
+		let reverse_transformations = quote::quote! {
+			match ret {
+				Ok(fin) => fin.as_raw(),
+				Err(e) => {
+					jni_toolbox::error::throw_error(&mut #env_iden, &e);
+					#return_expr
+				}
+			}
+		};
+		(error_handling, reverse_transformations)
+	} else {
+		let throw = if let Some(exception) = &attrs.exception {
+			quote::quote!( jni_toolbox::error::throw_exception(&mut #env_iden, #exception, #exception_message); )
+		} else if !attrs.errors.is_empty() {
+			quote::quote!( jni_toolbox::error::throw_mapped(&mut #env_iden, &e, &[ #(#mappings)* ], #message_format); )
+		} else {
+			quote::quote!( jni_toolbox::error::Throw::throw(&e, &mut #env_iden, #message_format); )
+		};
+		let error_handling = if ret.result {
 			quote::quote! {
 				let ret = match result {
 					Ok(x) => x,
-					Err(e) => match #env_iden.throw_new(e.jclass(), format!("{e:?}")) {
-						Err(e) => panic!("error throwing Java exception -- failed throwing: {e}"),
-						Ok(_) => return #return_expr
-					}
+					Err(e) => {
+						#throw
+						return #return_expr;
+					},
 				};
 			}
-		}
-	} else {
-		quote::quote!( let ret = result; )
-	};
-
-
-	let reverse_transformations = quote::quote! {
-		match ret.into_java(&mut #env_iden) {
-			Ok(fin) => fin,
-			Err(e) => {
-				// TODO should we panic instead?
-				let _ = #env_iden.throw_new(e.jclass(), format!("{e:?}"));
-				#return_expr
+		} else {
+			quote::quote!( let ret = result; )
+		};
+		let into_java = into_java(quote::quote!(ret));
+		let reverse_transformations = quote::quote! {
+			match #into_java {
+				Ok(fin) => fin,
+				Err(e) => {
+					jni_toolbox::error::throw_error(&mut #env_iden, &e);
+					#return_expr
+				}
 			}
-		}
+		};
+		(error_handling, reverse_transformations)
 	};
 
-	let inline_macro = if attrs.inline {
-		quote::quote!(#[inline])
-	} else {
-		quote::quote!()
+	let panic_policy = match attrs.panic {
+		None => quote::quote!( None ),
+		Some(PanicPolicy::Throw) => quote::quote!( Some(jni_toolbox::panic::PanicPolicy::Throw) ),
+		Some(PanicPolicy::Abort) => quote::quote!( Some(jni_toolbox::panic::PanicPolicy::Abort) ),
 	};
 
-	Ok(quote::quote! {
-		#inline_macro
-		#input
-
+	let function = quote::quote! {
 		#header {
+			// unwinding through an extern "system" function aborts the JVM, so catch any panic here
+			let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+
+				#transformations
 
-			#transformations
+				#invocation
 
-			#invocation
+				#error_handling
 
-			#error_handling
+				#reverse_transformations
 
-			#reverse_transformations
+			}));
 
+			match result {
+				Ok(ret) => ret,
+				Err(payload) => {
+					jni_toolbox::panic::handle_panic(&mut #env_iden, payload, #panic_policy);
+					#return_expr
+				},
+			}
 		}
+	};
+
+	Ok(Wrapper { function, registration })
+}
+
+fn mentions_self(tokens: TokenStream) -> bool {
+	tokens.into_iter().any(|token| match token {
+		proc_macro2::TokenTree::Ident(ident) => ident == "Self",
+		proc_macro2::TokenTree::Group(group) => mentions_self(group.stream()),
+		_ => false,
 	})
 }
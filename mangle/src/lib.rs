@@ -0,0 +1,58 @@
+//! JNI symbol name mangling, as described by the
+//! [JNI specification](https://docs.oracle.com/en/java/javase/21/docs/specs/jni/design.html#resolving-native-method-names).
+//!
+//! Shared by `jni-toolbox-macro` and `jni-toolbox`, which re-exports it as `jni_toolbox::mangle`,
+//! so that generated symbols and tools reusing these functions always agree.
+
+/// Escapes a single name component, such as a method name or a fully qualified class name.
+///
+/// Both `.` and `/` are treated as package separators and become `_`, while `_`, `;` and `[`
+/// are escaped as `_1`, `_2` and `_3`. Any other character which is not an ASCII letter or digit
+/// is escaped as `_0xxxx`, with one escape per UTF-16 code unit.
+pub fn escape(name: &str) -> String {
+	let mut out = String::with_capacity(name.len());
+	for c in name.chars() {
+		match c {
+			'.' | '/' => out.push('_'),
+			'_' => out.push_str("_1"),
+			';' => out.push_str("_2"),
+			'[' => out.push_str("_3"),
+			c if c.is_ascii_alphanumeric() => out.push(c),
+			c => {
+				let mut units = [0u16; 2];
+				for unit in c.encode_utf16(&mut units) {
+					out.push_str(&format!("_0{unit:04x}"));
+				}
+			},
+		}
+	}
+	out
+}
+
+/// Mangles a class name, given its package (either dotted or slashed, may be empty) and its
+/// binary name inside the package (so nested classes should be written as `Outer$Inner`).
+pub fn class(package: &str, class: &str) -> String {
+	if package.is_empty() {
+		escape(class)
+	} else {
+		format!("{}_{}", escape(package), escape(class))
+	}
+}
+
+/// The short JNI symbol for a native method, in the form `Java_<class>_<method>`.
+pub fn short_name(package: &str, class: &str, method: &str) -> String {
+	format!("Java_{}_{}", self::class(package, class), escape(method))
+}
+
+/// The long JNI symbol for a native method, in the form `Java_<class>_<method>__<arguments>`,
+/// used to tell overloaded methods apart.
+///
+/// The signature may be either the full method descriptor, such as `(ILjava/lang/String;)V`,
+/// or just the arguments part, such as `ILjava/lang/String;`.
+pub fn long_name(package: &str, class: &str, method: &str, signature: &str) -> String {
+	let arguments = match signature.strip_prefix('(') {
+		Some(rest) => rest.split(')').next().unwrap_or_default(),
+		None => signature,
+	};
+	format!("{}__{}", short_name(package, class, method), escape(arguments))
+}
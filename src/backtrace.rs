@@ -0,0 +1,150 @@
+use std::{backtrace::{Backtrace, BacktraceStatus}, sync::atomic::{AtomicBool, Ordering}};
+
+use jni::objects::{JObjectArray, JThrowable, JValueGen};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables attaching Rust backtraces to thrown exceptions, disabled by default.
+///
+/// When enabled, the Rust frames are added to the stack trace of every exception created for an
+/// error, before the frame of the native method. The frames come from the backtrace given by
+/// [`crate::JniToolboxError::backtrace`]: errors which don't capture one keep their Java stack trace.
+pub fn set_enabled(enabled: bool) {
+	ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether Rust backtraces are attached to thrown exceptions.
+pub fn enabled() -> bool {
+	ENABLED.load(Ordering::Relaxed)
+}
+
+/// A single Rust frame, as shown by `Backtrace`'s `Display`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+	/// The module path, or type for methods: used as declaring class.
+	pub module: String,
+	/// The name of the function.
+	pub function: String,
+	/// The name of the source file, if known.
+	pub file: Option<String>,
+	/// The line in the source file, if known.
+	pub line: Option<i32>,
+}
+
+/// Splits a symbol such as `my_crate::module::function` at its last path separator, ignoring
+/// separators inside generics (e.g. `<T as my_crate::Trait>::function`).
+fn split_symbol(symbol: &str) -> (&str, &str) {
+	let mut depth = 0i32;
+	let mut split = None;
+	let bytes = symbol.as_bytes();
+	for (n, c) in bytes.iter().enumerate() {
+		match c {
+			b'<' => depth += 1,
+			b'>' => depth -= 1,
+			b':' if depth == 0 && bytes.get(n + 1) == Some(&b':') => split = Some(n),
+			_ => {},
+		}
+	}
+	match split {
+		Some(n) => (&symbol[..n], &symbol[n + 2..]),
+		None => ("", symbol),
+	}
+}
+
+/// Whether the symbol belongs to this crate or to the standard library (such as the unwinding
+/// machinery of `catch_unwind`), and should be hidden from Java.
+fn is_hidden(symbol: &str) -> bool {
+	const HIDDEN: [&str; 5] = ["jni_toolbox", "std", "core", "alloc", "__rustc"];
+	let krate = symbol.trim_start_matches('<').split("::").next().unwrap_or_default();
+	HIDDEN.contains(&krate) || symbol.contains(" as jni_toolbox::") || symbol.starts_with("__rust")
+}
+
+/// Whether the symbol is the generated `Java_*` wrapper, or a closure inside it.
+fn is_wrapper(symbol: &str) -> bool {
+	symbol.split("::").any(|part| part.starts_with("Java_"))
+}
+
+/// Parses the frames of a captured backtrace, from the innermost one up to the generated `Java_*`
+/// wrapper, which is left out as the Java stack trace already has the native method frame.
+/// Frames inside this crate or the standard library, and frames without a module, are skipped.
+///
+/// This relies on the `Display` output of `Backtrace`, whose format isn't guaranteed by std: lines
+/// which can't be parsed are ignored, so a format change can only lose frames.
+pub fn frames(backtrace: &Backtrace) -> Vec<Frame> {
+	let mut frames = Vec::new();
+	let mut current: Option<Frame> = None;
+	for line in backtrace.to_string().lines() {
+		let line = line.trim();
+		if let Some(location) = line.strip_prefix("at ") {
+			if let Some(frame) = current.as_mut() {
+				// path:line:column, where the path may contain colons too
+				let mut parts = location.rsplitn(3, ':');
+				let _column = parts.next();
+				frame.line = parts.next().and_then(|l| l.parse().ok());
+				frame.file = parts.next().map(|f| f.rsplit(['/', '\\']).next().unwrap_or(f).to_string());
+			}
+			continue;
+		}
+		let Some((index, symbol)) = line.split_once(": ") else { continue };
+		if index.parse::<usize>().is_err() { continue };
+		frames.extend(current.take());
+		// frames past the generated entry point belong to the wrapper and to the JVM
+		if is_wrapper(symbol) { return frames };
+		if is_hidden(symbol) { continue };
+		let (module, function) = split_symbol(symbol);
+		if module.is_empty() { continue };
+		current = Some(Frame { module: module.to_string(), function: function.to_string(), file: None, line: None });
+	}
+	frames.extend(current);
+	frames
+}
+
+/// Adds the Rust frames to the given throwable, before the native method frame. Exceptions not
+/// created by a native method, such as rethrown Java exceptions, are left untouched.
+pub(crate) fn attach(env: &mut jni::JNIEnv, throwable: &JThrowable, backtrace: Option<&Backtrace>) {
+	if !enabled() { return };
+	// capturing here would be useless, as the frames where the error happened have already returned
+	let Some(backtrace) = backtrace.filter(|b| b.status() == BacktraceStatus::Captured) else { return };
+	let frames = frames(backtrace);
+	if frames.is_empty() { return };
+	if set_stack_trace(env, throwable, &frames).is_err() {
+		// the exception is still worth throwing without Rust frames
+		let _ = env.exception_clear();
+	}
+}
+
+fn set_stack_trace(env: &mut jni::JNIEnv, throwable: &JThrowable, frames: &[Frame]) -> Result<(), jni::errors::Error> {
+	const ELEMENT: &str = "java/lang/StackTraceElement";
+	let original = JObjectArray::from(env.call_method(throwable, "getStackTrace", "()[Ljava/lang/StackTraceElement;", &[])?.l()?);
+	let length = env.get_array_length(&original)?;
+	if length == 0 { return Ok(()) };
+	let top = env.get_object_array_element(&original, 0)?;
+	if !env.call_method(&top, "isNativeMethod", "()Z", &[])?.z()? { return Ok(()) };
+
+	let trace = env.new_object_array(frames.len() as i32 + length, ELEMENT, jni::objects::JObject::null())?;
+	for (n, frame) in frames.iter().enumerate() {
+		let class = env.new_string(&frame.module)?;
+		let method = env.new_string(&frame.function)?;
+		let file = match &frame.file {
+			Some(file) => env.new_string(file)?.into(),
+			None => jni::objects::JObject::null(),
+		};
+		let element = env.new_object(ELEMENT, "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;I)V", &[
+			JValueGen::Object(&class),
+			JValueGen::Object(&method),
+			JValueGen::Object(&file),
+			JValueGen::Int(frame.line.unwrap_or(-1)),
+		])?;
+		env.set_object_array_element(&trace, n as i32, &element)?;
+		for local in [class.into(), method.into(), file, element] {
+			env.delete_local_ref(local)?;
+		}
+	}
+	for n in 0..length {
+		let element = env.get_object_array_element(&original, n)?;
+		env.set_object_array_element(&trace, frames.len() as i32 + n, &element)?;
+		env.delete_local_ref(element)?;
+	}
+	env.call_method(throwable, "setStackTrace", "([Ljava/lang/StackTraceElement;)V", &[JValueGen::Object(&trace)])?;
+	Ok(())
+}
@@ -0,0 +1,15 @@
+/// Describes the native method being called. Exported functions can take it as a parameter,
+/// without needing the `JNIEnv`, for example to log or report which method failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallInfo {
+	/// The binary name of the class declaring the method, slashed, such as `my/package/Outer$Inner`.
+	pub class: &'static str,
+	/// The name of the Java method.
+	pub method: &'static str,
+}
+
+impl std::fmt::Display for CallInfo {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}.{}", self.class.replace(['/', '$'], "."), self.method)
+	}
+}
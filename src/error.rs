@@ -0,0 +1,535 @@
+use std::{any::TypeId, sync::RwLock};
+
+use jni::objects::{JThrowable, JValueGen};
+
+/// An error that is meant to be used with jni-toolbox.
+pub trait JniToolboxError: std::error::Error {
+	/// The Java class for the matching exception.
+	fn jclass(&self) -> &str;
+
+	/// The message of the matching exception, by default the `Display` representation of the error.
+	fn message(&self) -> String {
+		self.to_string()
+	}
+
+	/// Builds the matching Java exception. By default, [`JniToolboxError::jclass`] is constructed
+	/// through a constructor taking a single `String`, [`JniToolboxError::message`].
+	///
+	/// Override this to use other constructors, for example to carry error codes or other fields.
+	fn to_throwable<'j>(&self, env: &mut jni::JNIEnv<'j>) -> Result<JThrowable<'j>, jni::errors::Error> {
+		new_throwable(env, self.jclass(), &self.message())
+	}
+
+	/// Whether the causes from `source()` should be attached to the exception built by
+	/// [`JniToolboxError::to_throwable`]. Return `false` when it rethrows an existing Java exception,
+	/// or already sets its own cause.
+	fn attach_causes(&self) -> bool {
+		true
+	}
+
+	/// The backtrace of where the error happened, if it was captured. Only used when attaching
+	/// Rust backtraces is enabled, see [`crate::backtrace::set_enabled`].
+	fn backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+		None
+	}
+}
+
+fn exception_pending(env: &mut jni::JNIEnv) -> bool {
+	env.exception_check().unwrap_or(false)
+}
+
+type Downcaster = for<'e> fn(&'e (dyn std::error::Error + 'static)) -> Option<&'e dyn JniToolboxError>;
+
+static ERROR_TYPES: RwLock<Vec<(TypeId, Downcaster)>> = RwLock::new(Vec::new());
+
+/// Registers an error type, so that its [`JniToolboxError::jclass`] is used when it shows up as
+/// the cause of another error. Errors defined by `jni` and by this crate are always recognized.
+pub fn register_error<E: JniToolboxError + 'static>() {
+	let mut types = ERROR_TYPES.write().unwrap_or_else(|e| e.into_inner());
+	if types.iter().any(|(id, _)| *id == TypeId::of::<E>()) { return };
+	types.push((TypeId::of::<E>(), |e| e.downcast_ref::<E>().map(|e| e as &dyn JniToolboxError)));
+}
+
+/// Recovers the [`JniToolboxError`] implementation of a type-erased error, if it's known.
+pub fn as_toolbox_error<'e>(error: &'e (dyn std::error::Error + 'static)) -> Option<&'e dyn JniToolboxError> {
+	if let Some(e) = error.downcast_ref::<jni::errors::Error>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<jni::errors::JniError>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<crate::peer::PeerError>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<ArgumentError>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<crate::exception::JavaException>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<std::io::Error>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<std::num::ParseIntError>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<std::num::ParseFloatError>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<std::num::TryFromIntError>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<std::str::ParseBoolError>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<std::str::Utf8Error>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<std::string::FromUtf8Error>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<std::string::FromUtf16Error>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<std::char::ParseCharError>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<std::char::CharTryFromError>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<std::net::AddrParseError>() { return Some(e) };
+	if let Some(e) = error.downcast_ref::<std::fmt::Error>() { return Some(e) };
+	ERROR_TYPES
+		.read()
+		.unwrap_or_else(|e| e.into_inner())
+		.iter()
+		.find_map(|(_, downcast)| downcast(error))
+}
+
+/// Constructs a Java exception of the given class, through its constructor taking a single `String`.
+pub fn new_throwable<'j>(env: &mut jni::JNIEnv<'j>, class: &str, msg: &str) -> Result<JThrowable<'j>, jni::errors::Error> {
+	let msg = env.new_string(msg)?;
+	let obj = env.new_object(class, "(Ljava/lang/String;)V", &[JValueGen::Object(&msg)])?;
+	Ok(JThrowable::from(obj))
+}
+
+/// Maps foreign error types onto Java exception classes, as generated from the `errors` attribute.
+/// Each entry checks whether a type-erased error is of a given type.
+pub type ErrorMapping = [(fn(&(dyn std::error::Error + 'static)) -> bool, &'static str)];
+
+/// Builds the exception for a single link of an error chain: mapped types first, then known
+/// [`JniToolboxError`]s, falling back to `RuntimeException`. Also returns whether causes can be
+/// attached to it, see [`JniToolboxError::attach_causes`].
+fn link_throwable<'j>(env: &mut jni::JNIEnv<'j>, error: &(dyn std::error::Error + 'static), mapping: &ErrorMapping) -> Result<(JThrowable<'j>, bool), jni::errors::Error> {
+	let throwable = if let Some((_, class)) = mapping.iter().find(|(is, _)| is(error)) {
+		new_throwable(env, class, &error.to_string()).map(|t| (t, true))
+	} else if let Some(e) = as_toolbox_error(error) {
+		e.to_throwable(env).map(|t| (t, e.attach_causes()))
+	} else {
+		new_throwable(env, "java/lang/RuntimeException", &error.to_string()).map(|t| (t, true))
+	};
+	match throwable {
+		Ok(x) => Ok(x),
+		Err(_) => {
+			// a broken cause shouldn't prevent throwing the actual error
+			env.exception_clear()?;
+			new_throwable(env, "java/lang/RuntimeException", &error.to_string()).map(|t| (t, true))
+		},
+	}
+}
+
+/// Builds one exception per error in the `source()` chain, linking them through `initCause`.
+/// The chain stops at the first exception causes can't be attached to, such as a rethrown one.
+fn throwable_chain<'j>(
+	env: &mut jni::JNIEnv<'j>,
+	(top, mut attach): (JThrowable<'j>, bool),
+	mut source: Option<&(dyn std::error::Error + 'static)>,
+	mapping: &ErrorMapping,
+) -> Result<JThrowable<'j>, jni::errors::Error> {
+	let mut chain = vec![top];
+	while let (true, Some(cause)) = (attach, source) {
+		let (link, link_attach) = link_throwable(env, cause, mapping)?;
+		chain.push(link);
+		attach = link_attach;
+		source = cause.source();
+	}
+	for pair in chain.windows(2) {
+		env.call_method(&pair[0], "initCause", "(Ljava/lang/Throwable;)Ljava/lang/Throwable;", &[JValueGen::Object(&pair[1])])?;
+	}
+	Ok(chain.swap_remove(0))
+}
+
+/// How the message of a thrown exception is built from the error, as set with the `message` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageFormat {
+	/// The `Display` representation of the error.
+	Display,
+	/// The `Debug` representation of the error.
+	Debug,
+	/// The `Display` representation of every error in the `source()` chain, separated by colons.
+	Chain,
+}
+
+impl MessageFormat {
+	/// Formats the given error.
+	pub fn format<E: std::error::Error + ?Sized>(self, error: &E) -> String {
+		match self {
+			MessageFormat::Display => error.to_string(),
+			MessageFormat::Debug => format!("{error:?}"),
+			MessageFormat::Chain => std::iter::once(error.to_string())
+				.chain(std::iter::successors(error.source(), |e| e.source()).map(|e| e.to_string()))
+				.collect::<Vec<_>>()
+				.join(": "),
+		}
+	}
+}
+
+/// Anything which can be thrown as a Java exception from generated code: every [`JniToolboxError`],
+/// plus type-erased errors such as `Box<dyn Error>` (and, with the matching features, `anyhow::Error`
+/// and `eyre::Report`).
+pub trait Throw {
+	/// Throws this as a Java exception, unless one is already pending.
+	///
+	/// With a [`MessageFormat`], the exception is built through its constructor taking a single `String`,
+	/// instead of [`JniToolboxError::to_throwable`].
+	fn throw(&self, env: &mut jni::JNIEnv, format: Option<MessageFormat>);
+
+	/// The class, with slashes, and the message of the exception [`Throw::throw`] would throw.
+	fn describe(&self, format: Option<MessageFormat>) -> (String, String);
+}
+
+impl<E: JniToolboxError + ?Sized> Throw for E {
+	fn throw(&self, env: &mut jni::JNIEnv, format: Option<MessageFormat>) {
+		if exception_pending(env) { return };
+		let top = match format {
+			None => self.to_throwable(env).map(|t| (t, self.attach_causes())),
+			Some(format) => new_throwable(env, self.jclass(), &format.format(self)).map(|t| (t, true)),
+		};
+		let result = top
+			.inspect(|(top, _)| crate::backtrace::attach(env, top, self.backtrace()))
+			.and_then(|top| throwable_chain(env, top, self.source(), &[]))
+			.and_then(|throwable| env.throw(throwable));
+		if let Err(e) = result {
+			throw_failed(env, self.jclass(), e);
+		}
+	}
+
+	fn describe(&self, format: Option<MessageFormat>) -> (String, String) {
+		let message = match format {
+			None => self.message(),
+			Some(format) => format.format(self),
+		};
+		(self.jclass().to_string(), message)
+	}
+}
+
+impl Throw for Box<dyn std::error::Error + Send + Sync> {
+	fn throw(&self, env: &mut jni::JNIEnv, format: Option<MessageFormat>) {
+		throw_dyn_error(env, &**self, None, format);
+	}
+
+	fn describe(&self, format: Option<MessageFormat>) -> (String, String) {
+		describe_dyn_error(&**self, format)
+	}
+}
+
+impl Throw for Box<dyn std::error::Error> {
+	fn throw(&self, env: &mut jni::JNIEnv, format: Option<MessageFormat>) {
+		throw_dyn_error(env, &**self, None, format);
+	}
+
+	fn describe(&self, format: Option<MessageFormat>) -> (String, String) {
+		describe_dyn_error(&**self, format)
+	}
+}
+
+#[cfg(feature = "anyhow")]
+impl Throw for anyhow::Error {
+	fn throw(&self, env: &mut jni::JNIEnv, format: Option<MessageFormat>) {
+		throw_dyn_error(env, &**self, Some(self.backtrace()), format);
+	}
+
+	fn describe(&self, format: Option<MessageFormat>) -> (String, String) {
+		describe_dyn_error(&**self, format)
+	}
+}
+
+#[cfg(feature = "eyre")]
+impl Throw for eyre::Report {
+	fn throw(&self, env: &mut jni::JNIEnv, format: Option<MessageFormat>) {
+		throw_dyn_error(env, &**self, None, format);
+	}
+
+	fn describe(&self, format: Option<MessageFormat>) -> (String, String) {
+		describe_dyn_error(&**self, format)
+	}
+}
+
+/// Used in the generated code to throw an error as a Java exception. You probably didn't want
+/// to call this directly.
+///
+/// The error's `source()` chain is thrown too, as the chain of causes of the Java exception.
+///
+/// If a Java exception is already pending, for example because the error was caused by a Java
+/// method call, it is left untouched, so that its real type and stack trace reach the caller.
+pub fn throw_error<E: Throw + ?Sized>(env: &mut jni::JNIEnv, error: &E) {
+	error.throw(env, None);
+}
+
+/// Throws a type-erased error. The exception class is the one of the first error in the `source()`
+/// chain which is a known [`JniToolboxError`] (see [`register_error`]), falling back to `RuntimeException`,
+/// while the message is always the one of the outermost error.
+fn throw_dyn_error(
+	env: &mut jni::JNIEnv,
+	error: &(dyn std::error::Error + 'static),
+	backtrace: Option<&std::backtrace::Backtrace>,
+	format: Option<MessageFormat>,
+) {
+	if exception_pending(env) { return };
+	let (class, top) = match (as_toolbox_error(error), format) {
+		// the outermost error is known itself, let it build its own exception
+		(Some(known), None) => (known.jclass(), known.to_throwable(env)
+			.map(|t| (t, known.attach_causes()))
+			.inspect(|(top, _)| crate::backtrace::attach(env, top, known.backtrace().or(backtrace)))),
+		(_, format) => {
+			let class = dyn_error_class(error);
+			let msg = format.unwrap_or(MessageFormat::Display).format(error);
+			(class, new_throwable(env, class, &msg).map(|t| (t, true)).inspect(|(top, _)| crate::backtrace::attach(env, top, backtrace)))
+		},
+	};
+	let result = top
+		.and_then(|top| throwable_chain(env, top, error.source(), &[]))
+		.and_then(|throwable| env.throw(throwable));
+	if let Err(e) = result {
+		throw_failed(env, class, e);
+	}
+}
+
+/// The class of the first known [`JniToolboxError`] in the chain, or `RuntimeException`.
+fn dyn_error_class<'e>(error: &'e (dyn std::error::Error + 'static)) -> &'e str {
+	std::iter::successors(Some(error), |e| e.source())
+		.find_map(as_toolbox_error)
+		.map_or("java/lang/RuntimeException", |e| e.jclass())
+}
+
+fn describe_dyn_error(error: &(dyn std::error::Error + 'static), format: Option<MessageFormat>) -> (String, String) {
+	let message = match (as_toolbox_error(error), format) {
+		(Some(known), None) => known.message(),
+		(_, format) => format.unwrap_or(MessageFormat::Display).format(error),
+	};
+	(dyn_error_class(error).to_string(), message)
+}
+
+/// The class a mapped error is thrown as, see [`throw_mapped`].
+fn mapped_class(error: &(dyn std::error::Error + 'static), mapping: &ErrorMapping) -> &'static str {
+	mapping.iter()
+		.find(|(is, _)| is(error))
+		.map(|(_, class)| *class)
+		.unwrap_or("java/lang/RuntimeException")
+}
+
+/// Used in the generated code to describe an error which may not implement [`JniToolboxError`],
+/// through the mapping given with the `errors` attribute. See [`Throw::describe`].
+pub fn describe_mapped<E: std::error::Error + 'static>(error: &E, mapping: &ErrorMapping, format: Option<MessageFormat>) -> (String, String) {
+	let class = match (mapping.iter().any(|(is, _)| is(error)), as_toolbox_error(error)) {
+		(false, Some(known)) => known.jclass(),
+		_ => mapped_class(error, mapping),
+	};
+	(class.to_string(), format.unwrap_or(MessageFormat::Display).format(error))
+}
+
+/// Used in the generated code to throw an error which may not implement [`JniToolboxError`],
+/// through the mapping given with the `errors` attribute. You probably didn't want to call this directly.
+///
+/// Every link of the `source()` chain is checked against the mapping first, then against the known
+/// [`JniToolboxError`]s (see [`register_error`]), falling back to `RuntimeException`.
+pub fn throw_mapped<E: std::error::Error + 'static>(env: &mut jni::JNIEnv, error: &E, mapping: &ErrorMapping, format: Option<MessageFormat>) {
+	if exception_pending(env) { return };
+	let top = match format {
+		None => link_throwable(env, error, mapping),
+		Some(format) => {
+			let (class, message) = describe_mapped(error, mapping, Some(format));
+			new_throwable(env, &class, &message).map(|t| (t, true))
+		},
+	};
+	let result = top
+		.inspect(|(top, _)| crate::backtrace::attach(env, top, None))
+		.and_then(|top| throwable_chain(env, top, error.source(), mapping))
+		.and_then(|throwable| env.throw(throwable));
+	if let Err(e) = result {
+		throw_failed(env, "java/lang/RuntimeException", e);
+	}
+}
+
+/// Used in the generated code to throw a Java exception of the given class, unless one is
+/// already pending. You probably didn't want to call this directly.
+pub fn throw_exception(env: &mut jni::JNIEnv, class: &str, msg: String) {
+	if exception_pending(env) { return };
+	if let Err(e) = env.throw_new(class, msg) {
+		throw_failed(env, class, e);
+	}
+}
+
+/// Makes sure an exception is pending after failing to throw one, without panicking inside the
+/// `extern "system"` wrapper: if not even a plain `RuntimeException` can be thrown, the JVM is aborted.
+fn throw_failed(env: &mut jni::JNIEnv, class: &str, e: jni::errors::Error) {
+	// a failed lookup leaves its own exception (e.g. NoClassDefFoundError) pending, which is fine
+	if exception_pending(env) { return };
+	let msg = format!("error throwing Java exception {class}: {e}");
+	if env.throw_new("java/lang/RuntimeException", &msg).is_err() && !exception_pending(env) {
+		env.fatal_error(msg);
+	}
+}
+
+/// Maps a `jni` error onto the Java exception class to throw for it, or `None` to use the default.
+pub type JniErrorMapper = fn(&jni::errors::Error) -> Option<&'static str>;
+
+static JNI_ERROR_MAPPER: RwLock<Option<JniErrorMapper>> = RwLock::new(None);
+
+/// Registers a crate-wide mapper for `jni` errors, consulted before the default mapping.
+/// This allows routing errors coming from `jni` calls to your own exception hierarchy.
+///
+/// Errors wrapping a [`jni::errors::JniError`] reach the mapper as [`jni::errors::Error::JniCall`].
+/// Returns the mapper it replaces, if any.
+pub fn set_jni_error_mapper(mapper: JniErrorMapper) -> Option<JniErrorMapper> {
+	JNI_ERROR_MAPPER.write().unwrap_or_else(|e| e.into_inner()).replace(mapper)
+}
+
+/// Removes the mapper registered with [`set_jni_error_mapper`], returning it.
+pub fn take_jni_error_mapper() -> Option<JniErrorMapper> {
+	JNI_ERROR_MAPPER.write().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+impl JniToolboxError for jni::errors::Error {
+	fn jclass(&self) -> &str {
+		let mapper = *JNI_ERROR_MAPPER.read().unwrap_or_else(|e| e.into_inner());
+		if let Some(class) = mapper.and_then(|mapper| mapper(self)) {
+			return class;
+		}
+		match self {
+			jni::errors::Error::WrongJValueType(_, _) => "java/lang/ClassCastException",
+			jni::errors::Error::InvalidCtorReturn => "java/lang/IllegalArgumentException",
+			jni::errors::Error::InvalidArgList(_) => "java/lang/IllegalArgumentException",
+			jni::errors::Error::MethodNotFound { .. } => "java/lang/NoSuchMethodError",
+			jni::errors::Error::FieldNotFound { .. } => "java/lang/NoSuchFieldError",
+			// the original exception is still pending and is never replaced, see `throw_error`:
+			// this is only thrown if user code explicitly cleared it before returning the error
+			jni::errors::Error::JavaException => "java/lang/RuntimeException",
+			jni::errors::Error::JNIEnvMethodNotFound(_) => "java/lang/UnsupportedOperationException",
+			jni::errors::Error::NullPtr(_) => "java/lang/NullPointerException",
+			jni::errors::Error::NullDeref(_) => "java/lang/NullPointerException",
+			jni::errors::Error::TryLock => "java/lang/IllegalStateException",
+			jni::errors::Error::JavaVMMethodNotFound(_) => "java/lang/UnsupportedOperationException",
+			jni::errors::Error::FieldAlreadySet(_) => "java/lang/IllegalStateException",
+			jni::errors::Error::ThrowFailed(_) => "java/lang/RuntimeException",
+			jni::errors::Error::ParseFailed(_, _) => "java/lang/IllegalArgumentException",
+			jni::errors::Error::JniCall(e) => e.jclass(),
+		}
+	}
+}
+
+impl JniToolboxError for jni::errors::JniError {
+	fn jclass(&self) -> &str {
+		match self {
+			jni::errors::JniError::Unknown => "java/lang/RuntimeException",
+			jni::errors::JniError::ThreadDetached => "java/lang/IllegalStateException",
+			jni::errors::JniError::WrongVersion => "java/lang/UnsupportedOperationException",
+			jni::errors::JniError::NoMemory => "java/lang/OutOfMemoryError",
+			jni::errors::JniError::AlreadyCreated => "java/lang/IllegalStateException",
+			jni::errors::JniError::InvalidArguments => "java/lang/IllegalArgumentException",
+			jni::errors::JniError::Other(_) => "java/lang/RuntimeException",
+		}
+	}
+}
+
+impl JniToolboxError for std::io::Error {
+	fn jclass(&self) -> &str {
+		match self.kind() {
+			std::io::ErrorKind::NotFound => "java/io/FileNotFoundException",
+			std::io::ErrorKind::UnexpectedEof => "java/io/EOFException",
+			std::io::ErrorKind::Interrupted => "java/io/InterruptedIOException",
+			std::io::ErrorKind::TimedOut => "java/net/SocketTimeoutException",
+			_ => "java/io/IOException",
+		}
+	}
+}
+
+impl JniToolboxError for std::num::ParseIntError {
+	fn jclass(&self) -> &str {
+		"java/lang/NumberFormatException"
+	}
+}
+
+impl JniToolboxError for std::num::ParseFloatError {
+	fn jclass(&self) -> &str {
+		"java/lang/NumberFormatException"
+	}
+}
+
+impl JniToolboxError for std::num::TryFromIntError {
+	fn jclass(&self) -> &str {
+		// same as Math.toIntExact and friends
+		"java/lang/ArithmeticException"
+	}
+}
+
+impl JniToolboxError for std::str::ParseBoolError {
+	fn jclass(&self) -> &str {
+		"java/lang/IllegalArgumentException"
+	}
+}
+
+impl JniToolboxError for std::str::Utf8Error {
+	fn jclass(&self) -> &str {
+		"java/lang/IllegalArgumentException"
+	}
+}
+
+impl JniToolboxError for std::string::FromUtf8Error {
+	fn jclass(&self) -> &str {
+		"java/lang/IllegalArgumentException"
+	}
+}
+
+impl JniToolboxError for std::string::FromUtf16Error {
+	fn jclass(&self) -> &str {
+		"java/lang/IllegalArgumentException"
+	}
+}
+
+impl JniToolboxError for std::char::ParseCharError {
+	fn jclass(&self) -> &str {
+		"java/lang/IllegalArgumentException"
+	}
+}
+
+impl JniToolboxError for std::char::CharTryFromError {
+	fn jclass(&self) -> &str {
+		"java/lang/IllegalArgumentException"
+	}
+}
+
+impl JniToolboxError for std::net::AddrParseError {
+	fn jclass(&self) -> &str {
+		"java/lang/IllegalArgumentException"
+	}
+}
+
+impl JniToolboxError for std::fmt::Error {
+	fn jclass(&self) -> &str {
+		"java/lang/RuntimeException"
+	}
+}
+
+impl<T> JniToolboxError for std::sync::PoisonError<T> {
+	fn jclass(&self) -> &str {
+		"java/lang/IllegalStateException"
+	}
+}
+
+/// An argument which couldn't be converted from its Java value, thrown as the exception matching the
+/// underlying `jni` error, with a message pointing at the native method and parameter.
+#[derive(Debug)]
+pub struct ArgumentError {
+	/// The Java method, such as `my.package.MyClass.method`.
+	pub method: &'static str,
+	/// The position of the parameter in the Java method, starting from 1, or 0 for the object an instance method was called on.
+	pub position: usize,
+	/// The name of the parameter.
+	pub name: &'static str,
+	/// The Java type of the parameter, or the Rust one if it can't be inferred.
+	pub java_type: &'static str,
+	/// The conversion error.
+	pub source: jni::errors::Error,
+}
+
+impl std::fmt::Display for ArgumentError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.position {
+			0 => write!(f, "{}: invalid receiver '{}' of type {}: {}", self.method, self.name, self.java_type, self.source),
+			position => write!(f, "{}: invalid argument #{position} '{}' of type {}: {}", self.method, self.name, self.java_type, self.source),
+		}
+	}
+}
+
+impl std::error::Error for ArgumentError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(&self.source)
+	}
+}
+
+impl JniToolboxError for ArgumentError {
+	fn jclass(&self) -> &str {
+		self.source.jclass()
+	}
+}
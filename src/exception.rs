@@ -0,0 +1,173 @@
+use jni::objects::{GlobalRef, JObject, JObjectArray, JString, JThrowable};
+
+use crate::JniToolboxError;
+
+/// A Java exception caught while calling back into Java, captured as a regular Rust error.
+///
+/// Returning it from a `#[jni]` function rethrows the original throwable, unchanged.
+#[derive(Debug, Clone)]
+pub struct JavaException {
+	throwable: GlobalRef,
+	class: String,
+	hierarchy: Vec<String>,
+	message: Option<String>,
+	cause: Option<Box<JavaException>>,
+	stack_trace: Vec<String>,
+}
+
+/// Causes deeper than this are not captured, guarding against cyclic chains.
+const MAX_CAUSES: usize = 32;
+
+impl JavaException {
+	/// Takes the pending Java exception, if any, clearing it.
+	pub fn catch(env: &mut jni::JNIEnv) -> Result<Option<Self>, jni::errors::Error> {
+		if !env.exception_check()? {
+			return Ok(None);
+		}
+		let throwable = env.exception_occurred()?;
+		env.exception_clear()?;
+		Self::from_throwable(env, &throwable).map(Some)
+	}
+
+	/// Captures the given throwable, along with its causes.
+	pub fn from_throwable(env: &mut jni::JNIEnv, throwable: &JThrowable) -> Result<Self, jni::errors::Error> {
+		Self::capture(env, throwable, 0)
+	}
+
+	fn capture(env: &mut jni::JNIEnv, throwable: &JThrowable, depth: usize) -> Result<Self, jni::errors::Error> {
+		env.with_local_frame(16, |env| {
+			let mut hierarchy = Vec::new();
+			let mut class = Some(env.get_object_class(throwable)?);
+			while let Some(current) = class {
+				let name = env.call_method(&current, "getName", "()Ljava/lang/String;", &[])?.l()?;
+				hierarchy.push(string(env, name)?.unwrap_or_default().replace('.', "/"));
+				class = env.get_superclass(&current)?;
+			}
+
+			let message = env.call_method(throwable, "getMessage", "()Ljava/lang/String;", &[])?.l()?;
+			let message = string(env, message)?;
+
+			let cause = env.call_method(throwable, "getCause", "()Ljava/lang/Throwable;", &[])?.l()?;
+			let cause = if cause.is_null() || depth >= MAX_CAUSES || env.is_same_object(&cause, throwable)? {
+				None
+			} else {
+				Some(Box::new(Self::capture(env, &JThrowable::from(cause), depth + 1)?))
+			};
+
+			let trace = env.call_method(throwable, "getStackTrace", "()[Ljava/lang/StackTraceElement;", &[])?.l()?;
+			let trace = JObjectArray::from(trace);
+			let mut stack_trace = Vec::new();
+			for n in 0..env.get_array_length(&trace)? {
+				let element = env.get_object_array_element(&trace, n)?;
+				let text = env.call_method(&element, "toString", "()Ljava/lang/String;", &[])?.l()?;
+				stack_trace.push(string(env, text)?.unwrap_or_default());
+				env.delete_local_ref(element)?;
+			}
+
+			Ok(Self {
+				throwable: env.new_global_ref(throwable)?,
+				class: hierarchy.first().cloned().unwrap_or_default(),
+				hierarchy,
+				message,
+				cause,
+				stack_trace,
+			})
+		})
+	}
+
+	/// The fully qualified class of the exception, with slashes (e.g. `java/io/IOException`).
+	pub fn class(&self) -> &str {
+		&self.class
+	}
+
+	/// Whether the exception is an instance of the given class, either dotted or slashed.
+	pub fn is_instance_of(&self, class: &str) -> bool {
+		self.hierarchy.contains(&class.replace('.', "/"))
+	}
+
+	/// The message of the exception, as returned by `getMessage()`.
+	pub fn message(&self) -> Option<&str> {
+		self.message.as_deref()
+	}
+
+	/// The cause of the exception, as returned by `getCause()`.
+	pub fn cause(&self) -> Option<&JavaException> {
+		self.cause.as_deref()
+	}
+
+	/// The stack trace of the exception, one `StackTraceElement` per line.
+	pub fn stack_trace(&self) -> &[String] {
+		&self.stack_trace
+	}
+
+	/// The original throwable.
+	pub fn throwable(&self) -> &GlobalRef {
+		&self.throwable
+	}
+}
+
+fn string(env: &mut jni::JNIEnv, obj: JObject) -> Result<Option<String>, jni::errors::Error> {
+	if obj.is_null() {
+		return Ok(None);
+	}
+	let obj = JString::from(obj);
+	let res = env.get_string(&obj)?.into();
+	env.delete_local_ref(obj)?;
+	Ok(Some(res))
+}
+
+impl std::fmt::Display for JavaException {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		// same format as Throwable.toString()
+		let class = self.class.replace('/', ".");
+		match &self.message {
+			Some(message) => write!(f, "{class}: {message}"),
+			None => write!(f, "{class}"),
+		}
+	}
+}
+
+impl std::error::Error for JavaException {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		self.cause.as_deref().map(|e| e as &(dyn std::error::Error + 'static))
+	}
+}
+
+impl JniToolboxError for JavaException {
+	fn jclass(&self) -> &str {
+		&self.class
+	}
+
+	fn message(&self) -> String {
+		self.message.clone().unwrap_or_default()
+	}
+
+	fn to_throwable<'j>(&self, env: &mut jni::JNIEnv<'j>) -> Result<JThrowable<'j>, jni::errors::Error> {
+		Ok(JThrowable::from(env.new_local_ref(&self.throwable)?))
+	}
+
+	fn attach_causes(&self) -> bool {
+		// rethrown unchanged, with its own causes
+		false
+	}
+}
+
+/// Extension for results of `jni` calls, capturing the Java exception they may have thrown.
+pub trait CatchException<T> {
+	/// If the call threw, takes the pending Java exception as the inner error. Other `jni` errors
+	/// are left untouched as the outer one.
+	fn catch_exception(self, env: &mut jni::JNIEnv) -> Result<Result<T, JavaException>, jni::errors::Error>;
+}
+
+impl<T> CatchException<T> for Result<T, jni::errors::Error> {
+	fn catch_exception(self, env: &mut jni::JNIEnv) -> Result<Result<T, JavaException>, jni::errors::Error> {
+		match self {
+			Ok(x) => Ok(Ok(x)),
+			Err(jni::errors::Error::JavaException) => match JavaException::catch(env)? {
+				Some(e) => Ok(Err(e)),
+				None => Err(jni::errors::Error::JavaException),
+			},
+			Err(e) => Err(e),
+		}
+	}
+}
@@ -8,6 +8,12 @@ pub fn from_java_static<'j, T: FromJava<'j>>(env: &mut jni::JNIEnv<'j>, val: T::
 }
 
 /// Specifies how a Java type should be converted before being fed to Rust.
+#[diagnostic::on_unimplemented(
+	message = "`{Self}` can't be received from Java",
+	label = "this parameter type has no Java counterpart",
+	note = "implement `jni_toolbox::FromJava` for `{Self}`, picking the JNI type it's converted from as `From`",
+	note = "or receive a JNI type, such as `JObject`, and convert it inside the function",
+)]
 pub trait FromJava<'j> : Sized {
 	/// The JNI type representing the input.
 	type From : Sized;
@@ -52,7 +58,7 @@ impl<'j> FromJava<'j> for char {
 
 	#[inline]
 	fn from_java(_: &mut jni::JNIEnv, value: Self::From) -> Result<Self, jni::errors::Error> {
-		char::from_u32(value.into()).ok_or_else(|| jni::errors::Error::WrongJValueType("char", "invalid u16"))
+		char::from_u32(value.into()).ok_or(jni::errors::Error::WrongJValueType("char", "invalid u16"))
 	}
 }
 
@@ -2,13 +2,58 @@ use jni::objects::JObject;
 
 
 /// Specifies how a Rust type should be converted into a Java primitive.
+#[diagnostic::on_unimplemented(
+	message = "`{Self}` can't be returned to Java",
+	label = "this return type has no Java counterpart",
+	note = "implement `jni_toolbox::IntoJavaObject` for `{Self}` to return it as an object of a Java class",
+	note = "or implement `jni_toolbox::IntoJava` for `{Self}`, picking the JNI type it's converted into as `Ret`",
+)]
 pub trait IntoJava<'j> {
 	/// The JNI type representing the output.
-	type Ret;
+	type Ret: JniDefault;
 	/// Attempts to convert this Rust object into a Java primitive.
 	fn into_java(self, _: &mut jni::JNIEnv<'j>) -> Result<Self::Ret, jni::errors::Error>;
 }
 
+/// A JNI type with a value to return when a native method fails. Java never sees it, as an exception
+/// is pending, so it's just `0` for primitives and `null` for objects.
+pub trait JniDefault {
+	/// The value returned when a native method throws.
+	fn jni_default() -> Self;
+}
+
+macro_rules! zero_jni_default {
+	($($t: ty => $zero: expr),*) => {
+		$(
+			impl JniDefault for $t {
+				#[inline]
+				fn jni_default() -> Self {
+					$zero
+				}
+			}
+		)*
+	};
+}
+
+zero_jni_default!(
+	jni::sys::jlong => 0,
+	jni::sys::jint => 0,
+	jni::sys::jshort => 0,
+	jni::sys::jbyte => 0,
+	jni::sys::jchar => 0,
+	jni::sys::jboolean => 0,
+	jni::sys::jfloat => 0.0,
+	jni::sys::jdouble => 0.0,
+	() => ()
+);
+
+impl<T> JniDefault for *mut T {
+	#[inline]
+	fn jni_default() -> Self {
+		std::ptr::null_mut()
+	}
+}
+
 macro_rules! auto_into_java {
 	($t: ty, $j: ty) => {
 		impl<'j> IntoJava<'j> for $t {
@@ -51,6 +96,11 @@ impl<'j, X: IntoJavaObject<'j>> IntoJava<'j> for X {
 }
 
 /// Specifies how a Rust type should be converted into a Java object.
+#[diagnostic::on_unimplemented(
+	message = "`{Self}` can't be returned to Java",
+	label = "this return type has no Java counterpart",
+	note = "implement `jni_toolbox::IntoJavaObject` for `{Self}`, naming the Java class it's converted into as `CLASS`",
+)]
 pub trait IntoJavaObject<'j> {
 	/// The Java class associated with this type.
 	const CLASS: &'static str;
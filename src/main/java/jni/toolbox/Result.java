@@ -0,0 +1,19 @@
+package jni.toolbox;
+
+/**
+ * The outcome of a native method returning errors as values, with {@code #[jni(errors = "value")]}.
+ * It's either {@link Ok}, holding the returned value, or {@link Err}, describing the exception which
+ * would have been thrown otherwise.
+ */
+public sealed interface Result<T> permits Result.Ok, Result.Err {
+	/** A successful outcome. Primitives are boxed, and methods returning nothing hold {@code null}. */
+	record Ok<T>(T value) implements Result<T> {}
+
+	/** A failed outcome, with the fully qualified name of the exception class and its message. */
+	record Err<T>(String exceptionClass, String message) implements Result<T> {}
+
+	/** Whether this is an {@link Ok}. */
+	default boolean isOk() {
+		return this instanceof Ok;
+	}
+}
@@ -0,0 +1,77 @@
+use std::sync::{atomic::{AtomicBool, Ordering}, RwLock};
+
+/// What generated wrappers do when the Rust function panics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanicPolicy {
+	/// Throw a Java exception carrying the panic message (the default).
+	Throw,
+	/// Abort the JVM through `FatalError`, reporting the panic message.
+	Abort,
+}
+
+static ABORT: AtomicBool = AtomicBool::new(false);
+static EXCEPTION: RwLock<&'static str> = RwLock::new("java/lang/Error");
+
+/// Sets the crate-wide panic policy, used by functions without an explicit `panic` attribute.
+pub fn set_policy(policy: PanicPolicy) {
+	ABORT.store(policy == PanicPolicy::Abort, Ordering::Relaxed);
+}
+
+/// The current crate-wide panic policy.
+pub fn policy() -> PanicPolicy {
+	if ABORT.load(Ordering::Relaxed) { PanicPolicy::Abort } else { PanicPolicy::Throw }
+}
+
+/// Sets the Java class thrown when a Rust function panics, `java/lang/Error` by default.
+/// It must have a constructor taking a single `String` argument.
+pub fn set_exception(class: &'static str) {
+	*EXCEPTION.write().unwrap_or_else(|e| e.into_inner()) = class;
+}
+
+/// The Java class currently thrown on panic.
+pub fn exception() -> &'static str {
+	*EXCEPTION.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn message(payload: &(dyn std::any::Any + Send)) -> &str {
+	if let Some(msg) = payload.downcast_ref::<&str>() {
+		msg
+	} else if let Some(msg) = payload.downcast_ref::<String>() {
+		msg
+	} else {
+		"unknown panic payload"
+	}
+}
+
+/// Throws the panic exception, with the exception which was pending when the panic happened as its cause.
+fn throw_panic(env: &mut jni::JNIEnv, msg: &str, cause: Option<jni::objects::JThrowable>) -> Result<(), jni::errors::Error> {
+	let throwable = crate::error::new_throwable(env, exception(), msg)?;
+	if let Some(cause) = cause {
+		env.call_method(&throwable, "initCause", "(Ljava/lang/Throwable;)Ljava/lang/Throwable;", &[(&cause).into()])?;
+	}
+	env.throw(throwable)
+}
+
+/// Used in the generated code to report a panic caught at the JNI boundary. You probably didn't
+/// want to call this directly.
+///
+/// If throwing the exception fails too, the JVM is aborted through `FatalError`.
+pub fn handle_panic(env: &mut jni::JNIEnv, payload: Box<dyn std::any::Any + Send>, policy: Option<PanicPolicy>) {
+	let msg = format!("Rust panic: {}", message(payload.as_ref()));
+	match policy.unwrap_or_else(self::policy) {
+		PanicPolicy::Abort => env.fatal_error(msg),
+		PanicPolicy::Throw => {
+			// the function may have panicked after a failed Java call, whose exception is still pending
+			let pending = env.exception_occurred().ok().filter(|e| !e.is_null());
+			if pending.is_some() && env.exception_clear().is_err() {
+				return; // the pending exception is thrown instead
+			}
+			if let Err(e) = throw_panic(env, &msg, pending) {
+				// whatever went wrong is still better reported as a Java exception
+				if !env.exception_check().unwrap_or(false) {
+					env.fatal_error(format!("{msg} (failed throwing Java exception: {e})"));
+				}
+			}
+		},
+	}
+}
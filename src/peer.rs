@@ -0,0 +1,160 @@
+use std::{any::{Any, TypeId}, cell::RefCell, collections::HashMap, sync::{Arc, Mutex, OnceLock, RwLock, TryLockError}};
+
+use crate::JniToolboxError;
+
+type Peer = Arc<RwLock<Box<dyn Any + Send + Sync>>>;
+
+#[derive(Default)]
+struct Registry {
+	last: i64,
+	peers: HashMap<i64, (TypeId, Peer)>,
+}
+
+fn registry() -> &'static Mutex<Registry> {
+	static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+	REGISTRY.get_or_init(Default::default)
+}
+
+fn lookup(handle: i64) -> Result<Peer, PeerError> {
+	if handle == 0 { return Err(PeerError::Null) };
+	registry()
+		.lock()
+		.map_err(|_| PeerError::Poisoned(handle))?
+		.peers
+		.get(&handle)
+		.map(|(_, peer)| peer.clone())
+		.ok_or(PeerError::Freed(handle))
+}
+
+/// Moves a Rust object into the peer registry, returning the handle that Java should keep.
+///
+/// Handles are never `0`, so Java code can use `0` to mark an object as uninitialized or freed.
+pub fn register<T: Send + Sync + 'static>(value: T) -> i64 {
+	let mut registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+	registry.last += 1;
+	let handle = registry.last;
+	registry.peers.insert(handle, (TypeId::of::<T>(), Arc::new(RwLock::new(Box::new(value)))));
+	handle
+}
+
+/// Removes a Rust object from the peer registry, dropping it.
+///
+/// If the object is currently borrowed by another thread, it is dropped once that borrow ends.
+pub fn release<T: 'static>(handle: i64) -> Result<(), PeerError> {
+	if handle == 0 { return Err(PeerError::Null) };
+	let mut registry = registry().lock().map_err(|_| PeerError::Poisoned(handle))?;
+	let (ty, _) = registry.peers.get(&handle).ok_or(PeerError::Freed(handle))?;
+	if *ty != TypeId::of::<T>() { return Err(PeerError::WrongType(handle)) };
+	let peer = registry.peers.remove(&handle);
+	drop(registry); // don't hold the registry while running arbitrary Drop code
+	drop(peer);
+	Ok(())
+}
+
+thread_local! {
+	/// Handles borrowed by the current thread, and whether mutably.
+	static BORROWED: RefCell<Vec<(i64, bool)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Records a borrow on the current thread until dropped, refusing re-entrant borrows that would deadlock.
+struct Borrow(i64);
+
+impl Borrow {
+	/// Returns whether the lock can be taken blocking: borrows from other threads are waited for,
+	/// but a thread waiting on its own borrow would never wake up.
+	fn start(handle: i64, mutable: bool) -> Result<(Self, bool), PeerError> {
+		BORROWED.with_borrow_mut(|borrowed| {
+			let mut held = borrowed.iter().filter(|(h, _)| *h == handle);
+			let nested = held.clone().next().is_some();
+			if nested && (mutable || held.any(|(_, m)| *m)) {
+				return Err(PeerError::Busy(handle));
+			}
+			borrowed.push((handle, mutable));
+			Ok((Borrow(handle), !nested))
+		})
+	}
+}
+
+impl Drop for Borrow {
+	fn drop(&mut self) {
+		BORROWED.with_borrow_mut(|borrowed| {
+			if let Some(i) = borrowed.iter().rposition(|(h, _)| *h == self.0) {
+				borrowed.remove(i);
+			}
+		});
+	}
+}
+
+fn try_lock<G>(handle: i64, result: Result<G, TryLockError<G>>) -> Result<G, PeerError> {
+	match result {
+		Ok(guard) => Ok(guard),
+		Err(TryLockError::Poisoned(_)) => Err(PeerError::Poisoned(handle)),
+		Err(TryLockError::WouldBlock) => Err(PeerError::Busy(handle)),
+	}
+}
+
+/// Borrows the Rust object behind a handle and runs the given closure on it.
+/// Used in the generated code for `&self` methods.
+///
+/// Borrows from other threads are waited for, while a re-entrant call from Java on the same thread
+/// fails with [`PeerError::Busy`] if the object is mutably borrowed, instead of deadlocking.
+pub fn with_peer<T: 'static, R>(handle: i64, f: impl FnOnce(&T) -> R) -> Result<R, PeerError> {
+	let peer = lookup(handle)?;
+	let (_borrow, blocking) = Borrow::start(handle, false)?;
+	let guard = if blocking {
+		peer.read().map_err(|_| PeerError::Poisoned(handle))?
+	} else {
+		// a nested read lock may block behind a waiting writer, which waits for the outer one
+		try_lock(handle, peer.try_read())?
+	};
+	let value = guard.downcast_ref::<T>().ok_or(PeerError::WrongType(handle))?;
+	Ok(f(value))
+}
+
+/// Mutably borrows the Rust object behind a handle and runs the given closure on it.
+/// Used in the generated code for `&mut self` methods.
+///
+/// Borrows from other threads are waited for, while a re-entrant call from Java on the same thread
+/// fails with [`PeerError::Busy`] instead of deadlocking.
+pub fn with_peer_mut<T: 'static, R>(handle: i64, f: impl FnOnce(&mut T) -> R) -> Result<R, PeerError> {
+	let peer = lookup(handle)?;
+	let (_borrow, _) = Borrow::start(handle, true)?;
+	let mut guard = peer.write().map_err(|_| PeerError::Poisoned(handle))?;
+	let value = guard.downcast_mut::<T>().ok_or(PeerError::WrongType(handle))?;
+	Ok(f(value))
+}
+
+/// An error resolving a native peer handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerError {
+	/// The handle is `0`.
+	Null,
+	/// The handle does not refer to a live object, most likely because it was already released.
+	Freed(i64),
+	/// The handle refers to an object of a different type.
+	WrongType(i64),
+	/// A thread panicked while holding the object.
+	Poisoned(i64),
+	/// The object is already borrowed by the current thread, through a re-entrant call.
+	Busy(i64),
+}
+
+impl std::fmt::Display for PeerError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			PeerError::Null => write!(f, "native handle is null"),
+			PeerError::Freed(h) => write!(f, "native handle {h} was already freed"),
+			PeerError::WrongType(h) => write!(f, "native handle {h} points to an object of another type"),
+			PeerError::Poisoned(h) => write!(f, "native handle {h} is poisoned by a previous panic"),
+			PeerError::Busy(h) => write!(f, "native handle {h} is already borrowed by this thread"),
+		}
+	}
+}
+
+impl std::error::Error for PeerError {}
+
+impl JniToolboxError for PeerError {
+	fn jclass(&self) -> &str {
+		"java/lang/IllegalStateException"
+	}
+}
@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use crate::JniToolboxError;
+
+#[doc(hidden)]
+pub use inventory;
+
+/// A native method which should be registered through `RegisterNatives` when the library is loaded.
+/// Generated by `#[jni]` when using registration mode, you probably don't need to build one by hand.
+pub struct NativeMethod {
+	/// The fully qualified class declaring the method, with slashes (e.g. `java/lang/Object`).
+	pub class: &'static str,
+	/// The Java name of the method.
+	pub name: &'static str,
+	/// The full method descriptor, such as `(ILjava/lang/String;)V`.
+	pub signature: &'static str,
+	/// Pointer to the `extern "system"` function implementing the method.
+	pub fn_ptr: *mut std::ffi::c_void,
+}
+
+// SAFETY: the function pointer is never dereferenced by us, only handed to the JVM
+unsafe impl Sync for NativeMethod {}
+
+inventory::collect!(NativeMethod);
+
+/// Registers every collected native method, grouped by class.
+///
+/// Methods are registered one by one, so that failures can point at the exact method which the
+/// JVM refused. Any Java exception raised while registering is cleared and reported in the error.
+pub fn register_natives(env: &mut jni::JNIEnv) -> Result<(), RegisterError> {
+	let mut classes: BTreeMap<&str, Vec<&NativeMethod>> = BTreeMap::new();
+	for method in inventory::iter::<NativeMethod> {
+		classes.entry(method.class).or_default().push(method);
+	}
+
+	for (class, methods) in classes {
+		let jclass = env.find_class(class).map_err(|_| {
+			let _ = env.exception_clear();
+			RegisterError::ClassNotFound(class)
+		})?;
+		for method in methods {
+			let native = jni::NativeMethod {
+				name: method.name.into(),
+				sig: method.signature.into(),
+				fn_ptr: method.fn_ptr,
+			};
+			env.register_native_methods(&jclass, &[native]).map_err(|_| {
+				let _ = env.exception_clear();
+				RegisterError::MethodNotFound { class, name: method.name, signature: method.signature }
+			})?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Implementation of `JNI_OnLoad` generated by [`crate::jni_onload`]: registers every native method,
+/// throwing an `UnsatisfiedLinkError` from `System.loadLibrary` if anything goes wrong.
+///
+/// # Safety
+/// `vm` must be the pointer the JVM passed to `JNI_OnLoad`.
+pub unsafe fn on_load(vm: *mut jni::sys::JavaVM) -> jni::sys::jint {
+	let Ok(vm) = jni::JavaVM::from_raw(vm) else { return jni::sys::JNI_ERR };
+	let Ok(mut env) = vm.get_env() else { return jni::sys::JNI_ERR };
+	match register_natives(&mut env) {
+		Ok(()) => jni::sys::JNI_VERSION_1_8,
+		Err(e) => {
+			let _ = env.throw_new(e.jclass(), e.to_string());
+			jni::sys::JNI_ERR
+		},
+	}
+}
+
+/// Generates a `JNI_OnLoad` function registering every `#[jni]` function compiled in registration mode.
+/// Invoke it once, at the root of your library crate.
+#[macro_export]
+macro_rules! jni_onload {
+	() => {
+		#[no_mangle]
+		pub unsafe extern "system" fn JNI_OnLoad(vm: *mut $crate::jni::sys::JavaVM, _reserved: *mut std::ffi::c_void) -> $crate::jni::sys::jint {
+			$crate::register::on_load(vm)
+		}
+	};
+}
+
+/// An error registering native methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterError {
+	/// The declaring class could not be found by the class loader of the library.
+	ClassNotFound(&'static str),
+	/// The class has no `native` method with this name and signature.
+	MethodNotFound {
+		class: &'static str,
+		name: &'static str,
+		signature: &'static str,
+	},
+}
+
+impl std::fmt::Display for RegisterError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			RegisterError::ClassNotFound(class) =>
+				write!(f, "failed registering natives: class {} not found", class.replace('/', ".")),
+			RegisterError::MethodNotFound { class, name, signature } =>
+				write!(f, "failed registering natives: no native method {}.{name}{signature}", class.replace('/', ".")),
+		}
+	}
+}
+
+impl std::error::Error for RegisterError {}
+
+impl JniToolboxError for RegisterError {
+	fn jclass(&self) -> &str {
+		"java/lang/UnsatisfiedLinkError"
+	}
+}
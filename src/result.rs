@@ -0,0 +1,76 @@
+use jni::objects::{JObject, JValueGen};
+
+/// The Java class of successful results, see `src/main/java/jni/toolbox/Result.java`.
+pub const OK_CLASS: &str = "jni/toolbox/Result$Ok";
+/// The Java class of failed results, see `src/main/java/jni/toolbox/Result.java`.
+pub const ERR_CLASS: &str = "jni/toolbox/Result$Err";
+
+/// A raw JNI value which can be boxed into an object, such as `jint` into `java.lang.Integer`.
+pub trait Boxed {
+	/// Boxes this value, if it's a primitive.
+	///
+	/// # Safety
+	/// A raw `jobject` must be null or a valid local reference of the current frame.
+	unsafe fn boxed<'j>(self, env: &mut jni::JNIEnv<'j>) -> Result<JObject<'j>, jni::errors::Error>;
+}
+
+macro_rules! auto_boxed {
+	($t: ty, $class: literal, $sig: literal, $variant: ident) => {
+		impl Boxed for $t {
+			unsafe fn boxed<'j>(self, env: &mut jni::JNIEnv<'j>) -> Result<JObject<'j>, jni::errors::Error> {
+				env.call_static_method($class, "valueOf", $sig, &[JValueGen::$variant(self)])?.l()
+			}
+		}
+	};
+}
+
+auto_boxed!(jni::sys::jint, "java/lang/Integer", "(I)Ljava/lang/Integer;", Int);
+auto_boxed!(jni::sys::jlong, "java/lang/Long", "(J)Ljava/lang/Long;", Long);
+auto_boxed!(jni::sys::jshort, "java/lang/Short", "(S)Ljava/lang/Short;", Short);
+auto_boxed!(jni::sys::jbyte, "java/lang/Byte", "(B)Ljava/lang/Byte;", Byte);
+auto_boxed!(jni::sys::jfloat, "java/lang/Float", "(F)Ljava/lang/Float;", Float);
+auto_boxed!(jni::sys::jdouble, "java/lang/Double", "(D)Ljava/lang/Double;", Double);
+auto_boxed!(jni::sys::jchar, "java/lang/Character", "(C)Ljava/lang/Character;", Char);
+
+impl Boxed for jni::sys::jboolean {
+	unsafe fn boxed<'j>(self, env: &mut jni::JNIEnv<'j>) -> Result<JObject<'j>, jni::errors::Error> {
+		env.call_static_method("java/lang/Boolean", "valueOf", "(Z)Ljava/lang/Boolean;", &[JValueGen::Bool(self)])?.l()
+	}
+}
+
+impl Boxed for jni::sys::jobject {
+	unsafe fn boxed<'j>(self, _: &mut jni::JNIEnv<'j>) -> Result<JObject<'j>, jni::errors::Error> {
+		// SAFETY: guaranteed by the caller
+		Ok(unsafe { JObject::from_raw(self) })
+	}
+}
+
+impl Boxed for () {
+	unsafe fn boxed<'j>(self, _: &mut jni::JNIEnv<'j>) -> Result<JObject<'j>, jni::errors::Error> {
+		Ok(JObject::null())
+	}
+}
+
+/// Used in the generated code to build a successful result. You probably didn't want to call this directly.
+///
+/// # Safety
+/// See [`Boxed::boxed`]: a raw `jobject` must be null or a valid local reference of the current frame.
+#[doc(hidden)]
+pub unsafe fn ok<'j, T: Boxed>(env: &mut jni::JNIEnv<'j>, value: T) -> Result<JObject<'j>, jni::errors::Error> {
+	// SAFETY: guaranteed by the caller
+	let value = unsafe { value.boxed(env) }?;
+	env.new_object(OK_CLASS, "(Ljava/lang/Object;)V", &[JValueGen::Object(&value)])
+}
+
+/// Used in the generated code to build a failed result. You probably didn't want to call this directly.
+///
+/// If a Java exception is pending, it's what really happened: it's cleared and described instead.
+pub fn err<'j>(env: &mut jni::JNIEnv<'j>, class: &str, message: &str) -> Result<JObject<'j>, jni::errors::Error> {
+	let (class, message) = match crate::exception::JavaException::catch(env)? {
+		Some(e) => (e.class().to_string(), e.message().unwrap_or_default().to_string()),
+		None => (class.to_string(), message.to_string()),
+	};
+	let class = env.new_string(class.replace('/', "."))?;
+	let message = env.new_string(message)?;
+	env.new_object(ERR_CLASS, "(Ljava/lang/String;Ljava/lang/String;)V", &[JValueGen::Object(&class), JValueGen::Object(&message)])
+}
@@ -0,0 +1,14 @@
+package toolbox;
+
+public class CodedException extends Exception {
+	private final int code;
+
+	public CodedException(String msg, int code) {
+		super(msg);
+		this.code = code;
+	}
+
+	public int getCode() {
+		return this.code;
+	}
+}
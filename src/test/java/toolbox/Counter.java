@@ -0,0 +1,23 @@
+package toolbox;
+
+public class Counter {
+	private long ptr;
+
+	public Counter() {
+		this.ptr = create();
+	}
+
+	public void close() {
+		free(this.ptr);
+	}
+
+	public long handle() {
+		return this.ptr;
+	}
+
+	private static native long create();
+	private static native void free(long ptr);
+	native int increment(int amount);
+	static native int peek(long handle);
+	static native int max_amount();
+}
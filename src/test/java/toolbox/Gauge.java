@@ -0,0 +1,23 @@
+package toolbox;
+
+public class Gauge {
+	private long ptr;
+
+	public Gauge() {
+		this.ptr = create();
+	}
+
+	public void close() {
+		free(this.ptr);
+	}
+
+	public long handle() {
+		return this.ptr;
+	}
+
+	private static native long create();
+	private static native void free(long ptr);
+	native int add(int amount);
+	native int nested();
+	static native int peek(long handle);
+}
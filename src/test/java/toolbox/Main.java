@@ -2,8 +2,10 @@ package toolbox;
 
 import org.junit.jupiter.api.Test;
 import static org.junit.jupiter.api.Assertions.assertEquals;
+import static org.junit.jupiter.api.Assertions.assertFalse;
 import static org.junit.jupiter.api.Assertions.assertNull;
 import static org.junit.jupiter.api.Assertions.assertThrows;
+import static org.junit.jupiter.api.Assertions.assertTrue;
 
 
 public class Main {
@@ -11,6 +13,8 @@ public class Main {
 		System.loadLibrary("jni_toolbox_test");
 	}
 
+	static final int ANSWER = 42;
+
 	static native int sum(int a, int b);
 	static native String concat(String a, String b);
 	static native String[] to_vec(String a, String b, String c);
@@ -18,6 +22,61 @@ public class Main {
 	static native String optional(boolean present);
 	static native String raw();
 	static native void throw_error();
+	static native void call_thrower();
+	static native String callback(String method);
+	static native String context();
+	static native String explicit_context(String env);
+	static native String class_name(String prefix);
+	native String this_class();
+	native boolean keep_this();
+	native int instance_answer();
+	native boolean detected_this();
+	native String described();
+	static native long one_second_later(long at);
+	static native double ratio(double a, double b);
+	static native long parse_id(String input);
+	static native short parse_small(String input);
+	static native int sumInt(int[] values);
+	static native int firstInt(int[] values);
+	static native int lastInt(int[] values);
+	static native double sumDouble(double[] values);
+	static native int largest(int[] values);
+	static native long largest(long[] values);
+	static native void backtraces(boolean enabled);
+	static native void traced();
+	static native void chain_message();
+	static native void debug_message();
+	static native void dotted_exception();
+	static native jni.toolbox.Result<Integer> checked_div(int a, int b);
+	static native jni.toolbox.Result<Void> checked_nothing(boolean fail);
+	static native jni.toolbox.Result<Integer> checked_rem(int a, int b);
+	static native int thrown_rem(int a, int b);
+	static native void chained();
+	static native void coded();
+	static native void coded_cause();
+	static native void derived(int kind);
+	static native int parse_number(String input);
+	static native String read_file(String path);
+	static native String foreign(String path);
+	static native void boxed();
+	static native int contextual(String input);
+	static native long wrong_type();
+	static native void install_mapper();
+	static native void restore_mapper();
+	static native void locked();
+	static native String panicking();
+	static native void panicking_after_throw();
+	static native int add(int a, int b);
+	static native long add(long a, long b);
+	static native String add(String a, String b);
+
+	static native int shared();
+	static native int shared_alias();
+
+	static class Nested {
+		static native int nested_answer();
+		static native int nested_shared();
+	}
 
 	@Test
 	public void argumentsByValue() {
@@ -67,4 +126,281 @@ public class Main {
 	public void throwError() {
 		assertThrows(CustomException.class, Main::throw_error);
 	}
+
+	@Test
+	public void peerMethods() {
+		Counter counter = new Counter();
+		assertEquals(counter.increment(3), 3);
+		assertEquals(counter.increment(4), 7);
+		assertEquals(Counter.peek(counter.handle()), 7);
+		counter.close();
+		assertThrows(IllegalStateException.class, () -> counter.increment(1));
+		assertThrows(IllegalStateException.class, () -> Counter.peek(counter.handle()));
+		assertThrows(IllegalStateException.class, () -> Counter.peek(0));
+	}
+
+	@Test
+	public void peerBlock() {
+		Gauge gauge = new Gauge();
+		assertEquals(gauge.add(3), 3);
+		assertEquals(Gauge.peek(gauge.handle()), 3);
+		assertThrows(IllegalStateException.class, () -> gauge.nested());
+		assertEquals(Gauge.peek(gauge.handle()), 3);
+		gauge.close();
+		assertThrows(IllegalStateException.class, () -> gauge.add(1));
+	}
+
+	static void thrower() {
+		throw new UnsupportedOperationException("thrown from java");
+	}
+
+	static void ioThrower() throws java.io.IOException {
+		throw new java.io.FileNotFoundException("missing");
+	}
+
+	static void causedThrower() {
+		throw new IllegalStateException("outer", new UnsupportedOperationException("inner"));
+	}
+
+	static void quiet() {}
+
+	@Test
+	public void preservesPendingException() {
+		UnsupportedOperationException e = assertThrows(UnsupportedOperationException.class, Main::call_thrower);
+		assertEquals(e.getMessage(), "thrown from java");
+		assertEquals(e.getStackTrace()[0].getMethodName(), "thrower");
+	}
+
+	@Test
+	public void chainsCauses() {
+		IllegalStateException e = assertThrows(IllegalStateException.class, Main::chained);
+		assertTrue(e.getCause() instanceof CustomException);
+		assertEquals(e.getCause().getMessage(), "some test error");
+		assertNull(e.getCause().getCause());
+	}
+
+	@Test
+	public void customThrowable() {
+		CodedException e = assertThrows(CodedException.class, Main::coded);
+		assertEquals(e.getCode(), 404);
+		assertEquals(e.getMessage(), "coded failure");
+		CodedException caused = assertThrows(CodedException.class, Main::coded_cause);
+		assertEquals(caused.getCode(), 500);
+		assertTrue(caused.getCause() instanceof CustomException);
+	}
+
+	@Test
+	public void derivedErrors() {
+		IllegalArgumentException e = assertThrows(IllegalArgumentException.class, () -> Main.derived(0));
+		assertEquals(e.getMessage(), "bad argument");
+		IndexOutOfBoundsException oob = assertThrows(IndexOutOfBoundsException.class, () -> Main.derived(1));
+		assertEquals(oob.getMessage(), "index 1 out of bounds");
+		CustomException custom = assertThrows(CustomException.class, () -> Main.derived(2));
+		assertEquals(custom.getMessage(), "some test error");
+	}
+
+	@Test
+	public void standardErrors() {
+		assertEquals(Main.parse_number("42"), 42);
+		assertThrows(NumberFormatException.class, () -> Main.parse_number("forty-two"));
+		assertThrows(java.io.FileNotFoundException.class, () -> Main.read_file("/nonexistent"));
+	}
+
+	@Test
+	public void mappedErrors() {
+		UnsupportedOperationException e = assertThrows(UnsupportedOperationException.class, () -> Main.foreign("/nonexistent"));
+		assertEquals(e.getMessage(), "foreign failure");
+		assertTrue(e.getCause() instanceof java.io.FileNotFoundException);
+	}
+
+	@Test
+	public void erasedErrors() {
+		assertThrows(CustomException.class, Main::boxed);
+		assertEquals(Main.contextual("42"), 42);
+		NumberFormatException e = assertThrows(NumberFormatException.class, () -> Main.contextual("forty-two"));
+		assertEquals(e.getMessage(), "invalid number");
+		assertTrue(e.getCause() instanceof NumberFormatException);
+		RuntimeException negative = assertThrows(RuntimeException.class, () -> Main.contextual("-1"));
+		assertEquals(negative.getMessage(), "negative number");
+	}
+
+	@Test
+	public void parameterRoles() {
+		assertEquals(Main.context(), "toolbox.Main.context attached=true");
+		assertEquals(Main.explicit_context("env"), "toolbox.Main.explicit_context env attached=true");
+		assertEquals(Main.class_name("class: "), "class: toolbox.Main");
+		assertEquals(this.this_class(), "toolbox.Main");
+		assertTrue(this.keep_this());
+	}
+
+	@Test
+	public void instanceMethods() {
+		assertEquals(this.instance_answer(), 42);
+		assertTrue(this.detected_this());
+		assertEquals(this.described(), this.toString());
+	}
+
+	@Test
+	public void customConversions() {
+		assertEquals(Main.one_second_later(1000L), 2000L);
+		IllegalArgumentException e = assertThrows(IllegalArgumentException.class, () -> Main.one_second_later(-1L));
+		assertTrue(e.getMessage().startsWith("toolbox.Main.one_second_later: invalid argument #1 'at'"));
+	}
+
+	@Test
+	public void failingPrimitives() {
+		assertEquals(Main.ratio(3.0, 2.0), 1.5);
+		assertThrows(ArithmeticException.class, () -> Main.ratio(1.0, 0.0));
+		assertEquals(Main.parse_id("12345678901"), 12345678901L);
+		assertThrows(NumberFormatException.class, () -> Main.parse_id("none"));
+		assertEquals(Main.parse_small("-7"), -7);
+		assertThrows(NumberFormatException.class, () -> Main.parse_small("70000"));
+	}
+
+	@Test
+	public void instantiatedGenerics() {
+		assertEquals(Main.sumInt(new int[] { 1, 2, 3 }), 6);
+		assertEquals(Main.sumDouble(new double[] { 0.5, 0.25 }), 0.75);
+		assertEquals(Main.largest(new int[] { 3, 9, 4 }), 9);
+		assertEquals(Main.largest(new long[] { 3L, 1L << 40 }), 1L << 40);
+		assertEquals(Main.firstInt(new int[] { 4, 5 }), 4);
+		assertEquals(Main.firstInt(new int[] {}), 0);
+		assertEquals(Main.lastInt(new int[] { 4, 5 }), 5);
+	}
+
+	@Test
+	public void multipleTargets() {
+		assertEquals(Main.shared(), 7);
+		assertEquals(Main.shared_alias(), 7);
+		assertEquals(Main.Nested.nested_shared(), 7);
+	}
+
+	@Test
+	public void capturedExceptions() {
+		assertEquals(Main.callback("quiet"), "nothing thrown");
+		assertEquals(Main.callback("ioThrower"), "java/io/FileNotFoundException: missing");
+		IllegalStateException e = assertThrows(IllegalStateException.class, () -> Main.callback("causedThrower"));
+		assertEquals(e.getMessage(), "outer");
+		assertTrue(e.getCause() instanceof UnsupportedOperationException);
+		assertEquals(e.getStackTrace()[0].getMethodName(), "causedThrower");
+	}
+
+	@Test
+	public void rustBacktraces() {
+		assertEquals(assertThrows(CustomException.class, Main::traced).getStackTrace()[0].getMethodName(), "traced");
+		Main.backtraces(true);
+		try {
+			StackTraceElement[] trace = assertThrows(CustomException.class, Main::traced).getStackTrace();
+			assertEquals(trace[0].getClassName(), "jni_toolbox_test::main");
+			assertEquals(trace[0].getMethodName(), "traced");
+			assertEquals(trace[0].getFileName(), "test.rs");
+			int nativeFrame = -1;
+			for (int i = 0; i < trace.length && nativeFrame < 0; i++) {
+				if (trace[i].isNativeMethod()) {
+					assertEquals(trace[i].getMethodName(), "traced");
+					nativeFrame = i;
+					continue;
+				}
+				String className = trace[i].getClassName();
+				assertFalse(className.isEmpty());
+				for (String hidden : new String[] { "jni_toolbox::", "std::", "core::", "alloc::", "<std::", "<core::", "<alloc::", "__rust" }) {
+					assertFalse(className.startsWith(hidden), className);
+				}
+				assertFalse(className.contains("Java_"), className);
+				assertFalse(trace[i].getMethodName().startsWith("Java_"));
+			}
+			assertEquals(nativeFrame, 1);
+			// errors without a backtrace of their own keep the Java stack trace
+			StackTraceElement[] plain = assertThrows(CustomException.class, Main::throw_error).getStackTrace();
+			assertTrue(plain[0].isNativeMethod());
+			assertEquals(plain[0].getMethodName(), "throw_error");
+		} finally {
+			Main.backtraces(false);
+		}
+	}
+
+	@Test
+	public void argumentMessages() {
+		NullPointerException e = assertThrows(NullPointerException.class, () -> Main.concat(null, "b"));
+		assertTrue(e.getMessage().startsWith("toolbox.Main.concat: invalid argument #1 'a' of type java.lang.String: "));
+		assertTrue(e.getCause() instanceof NullPointerException);
+	}
+
+	@Test
+	public void messageFormats() {
+		assertEquals(assertThrows(IllegalStateException.class, Main::chain_message).getMessage(), "outer test error: some test error");
+		assertEquals(assertThrows(IllegalStateException.class, Main::debug_message).getMessage(), "OuterError(CustomError)");
+	}
+
+	@Test
+	public void dottedException() {
+		assertEquals(assertThrows(IllegalStateException.class, Main::dotted_exception).getMessage(), "thrown by name");
+	}
+
+	@Test
+	public void errorsAsValues() {
+		jni.toolbox.Result<Integer> ok = Main.checked_div(42, 2);
+		assertTrue(ok.isOk());
+		assertEquals(((jni.toolbox.Result.Ok<Integer>) ok).value().intValue(), 21);
+		jni.toolbox.Result<Integer> err = Main.checked_div(42, 0);
+		assertFalse(err.isOk());
+		assertEquals(((jni.toolbox.Result.Err<Integer>) err).exceptionClass(), "java.lang.ArithmeticException");
+		assertEquals(((jni.toolbox.Result.Err<Integer>) err).message(), "division by zero");
+		assertNull(((jni.toolbox.Result.Ok<Void>) Main.checked_nothing(false)).value());
+		jni.toolbox.Result.Err<Void> custom = (jni.toolbox.Result.Err<Void>) Main.checked_nothing(true);
+		assertEquals(custom.exceptionClass(), "toolbox.CustomException");
+		assertEquals(custom.message(), "some test error");
+		assertEquals(((jni.toolbox.Result.Ok<Integer>) Main.checked_rem(5, 3)).value().intValue(), 2);
+		assertEquals(Main.thrown_rem(5, 3), 2);
+		assertThrows(ArithmeticException.class, () -> Main.thrown_rem(5, 0));
+	}
+
+	@Test
+	public void mapsJniErrors() {
+		assertThrows(ClassCastException.class, Main::wrong_type);
+		Main.install_mapper();
+		try {
+			assertThrows(CustomException.class, Main::locked);
+		} finally {
+			Main.restore_mapper();
+		}
+		assertThrows(IllegalStateException.class, Main::locked);
+	}
+
+	@Test
+	public void catchesPanics() {
+		Error e = assertThrows(Error.class, Main::panicking);
+		assertEquals(e.getMessage(), "Rust panic: something went wrong");
+		Error pending = assertThrows(Error.class, Main::panicking_after_throw);
+		assertEquals(pending.getMessage(), "Rust panic: ignored a Java exception");
+		assertTrue(pending.getCause() instanceof UnsupportedOperationException);
+	}
+
+	@Test
+	public void overloads() {
+		assertEquals(Main.add(1, 2), 3);
+		assertEquals(Main.add(1L << 40, 1L), (1L << 40) + 1);
+		assertEquals(Main.add("a", "b"), "ab");
+	}
+
+	@Test
+	public void registeredNatives() {
+		assertEquals(Registered.multiply(6, 7), 42);
+		assertEquals(((jni.toolbox.Result.Ok<Integer>) Registered.divide(42, 6)).value().intValue(), 7);
+		jni.toolbox.Result.Err<Integer> err = (jni.toolbox.Result.Err<Integer>) Registered.divide(42, 0);
+		assertEquals(err.exceptionClass(), "java.lang.ArithmeticException");
+		assertEquals(err.message(), "division by zero");
+		long greeter = Registered.greeter("hello");
+		assertEquals(Registered.greet(greeter, "world"), "hello world");
+	}
+
+	@Test
+	public void nestedClass() {
+		assertEquals(Main.Nested.nested_answer(), 42);
+	}
+
+	@Test
+	public void overriddenClass() {
+		assertEquals(Counter.max_amount(), Integer.MAX_VALUE);
+	}
 }
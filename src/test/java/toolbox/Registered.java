@@ -0,0 +1,12 @@
+package toolbox;
+
+public class Registered {
+	static {
+		System.loadLibrary("jni_toolbox_test");
+	}
+
+	static native int multiply(int a, int b);
+	static native jni.toolbox.Result<Integer> divide(int a, int b);
+	static native long greeter(String greeting);
+	static native String greet(long handle, String name);
+}
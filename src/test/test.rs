@@ -1,4 +1,6 @@
-use jni_toolbox::{jni, JniToolboxError};
+use jni_toolbox::{jni, FromJavaException, JavaException, JniToolboxError};
+
+jni_toolbox::jni_onload!();
 
 #[jni(package = "toolbox", class = "Main")]
 fn sum(a: i32, b: i32) -> i32 {
@@ -34,17 +36,496 @@ fn raw<'local>(env: &mut jni::JNIEnv<'local>) -> Result<jni::objects::JString<'l
 	env.new_string("hello world!")
 }
 
+#[jni(package = "toolbox", class = "Main")]
+fn throw_error() -> Result<(), CustomError> {
+	Err(CustomError)
+}
+
+#[jni(package = "toolbox", class = "Main")]
+mod main {
+	use super::CustomError;
+
+	pub fn chained() -> Result<(), super::OuterError> {
+		jni_toolbox::error::register_error::<CustomError>();
+		Err(super::OuterError(CustomError))
+	}
+
+	pub fn coded() -> Result<(), super::CodedError> {
+		Err(super::CodedError(404))
+	}
+
+	pub fn coded_cause() -> Result<(), super::CodedCauseError> {
+		jni_toolbox::error::register_error::<CustomError>();
+		Err(super::CodedCauseError(CustomError))
+	}
+
+	pub fn derived(kind: i32) -> Result<(), super::DerivedError> {
+		match kind {
+			0 => Err(super::DerivedError::BadArgument),
+			1 => Err(super::DerivedError::OutOfBounds(kind)),
+			_ => Err(super::DerivedError::Custom(CustomError)),
+		}
+	}
+
+	pub fn parse_number(input: String) -> Result<i32, std::num::ParseIntError> {
+		input.parse()
+	}
+
+	pub fn read_file(path: String) -> Result<String, std::io::Error> {
+		std::fs::read_to_string(path)
+	}
+
+	#[jni(errors(super::ForeignError => "java.lang.UnsupportedOperationException"))]
+	pub fn foreign(path: String) -> Result<String, super::ForeignError> {
+		std::fs::read_to_string(path).map_err(super::ForeignError)
+	}
+
+	pub fn boxed() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+		jni_toolbox::error::register_error::<CustomError>();
+		Err(Box::new(CustomError))
+	}
+
+	pub fn contextual(input: String) -> anyhow::Result<i32> {
+		use anyhow::Context;
+		let number: i32 = input.parse().context("invalid number")?;
+		anyhow::ensure!(number >= 0, "negative number");
+		Ok(number)
+	}
+
+	pub fn call_thrower(env: &mut jni::JNIEnv) -> Result<(), jni::errors::Error> {
+		env.call_static_method("toolbox/Main", "thrower", "()V", &[])?;
+		Ok(())
+	}
+
+	pub fn callback<'local>(env: &mut jni::JNIEnv<'local>, method: String) -> Result<String, super::CallbackError> {
+		use jni_toolbox::exception::CatchException;
+		let result = env.call_static_method("toolbox/Main", method, "()V", &[]).catch_exception(env)?;
+		match result.map_err(super::CallbackError::from) {
+			Ok(_) => Ok("nothing thrown".to_string()),
+			Err(super::CallbackError::Io(e)) => Ok(format!("{}: {}", e.class(), e.message().unwrap_or_default())),
+			Err(e) => Err(e),
+		}
+	}
+
+	pub fn context(info: jni_toolbox::CallInfo, vm: &jni::JavaVM) -> String {
+		format!("{info} attached={}", vm.get_env().is_ok())
+	}
+
+	pub fn explicit_context(#[call_info] meta: jni_toolbox::CallInfo, #[vm] jvm: &jni::JavaVM, env: String) -> String {
+		format!("{meta} {env} attached={}", jvm.get_env().is_ok())
+	}
+
+	pub fn class_name(prefix: String, #[class] class: jni::objects::JClass, #[env] env: &mut jni::JNIEnv) -> Result<String, jni::errors::Error> {
+		let name = env.call_method(&class, "getName", "()Ljava/lang/String;", &[])?.l()?;
+		let name: String = env.get_string(&name.into())?.into();
+		Ok(format!("{prefix}{name}"))
+	}
+
+	pub fn this_class(#[this] this: &jni::objects::JObject, #[env] env: &mut jni::JNIEnv) -> Result<String, jni::errors::Error> {
+		let class = env.get_object_class(this)?;
+		let name = env.call_method(&class, "getName", "()Ljava/lang/String;", &[])?.l()?;
+		Ok(env.get_string(&name.into())?.into())
+	}
+
+	pub fn keep_this(#[this] this: jni::objects::GlobalRef) -> bool {
+		!this.as_obj().is_null()
+	}
+
+	#[jni(instance)]
+	pub fn instance_answer() -> i32 {
+		42
+	}
+
+	pub fn detected_this(this: jni::objects::JObject, env: &mut jni::JNIEnv) -> Result<bool, jni::errors::Error> {
+		env.is_instance_of(&this, "toolbox/Main")
+	}
+
+	#[jni(instance)]
+	pub fn described(this: super::Described) -> String {
+		this.0
+	}
+
+	#[jni(ret_with = super::epoch_millis)]
+	pub fn one_second_later(#[jni(with = super::epoch_millis)] at: std::time::SystemTime) -> std::time::SystemTime {
+		at + std::time::Duration::from_secs(1)
+	}
+
+	#[jni(exception = "java.lang.ArithmeticException", message = "display")]
+	pub fn ratio(a: f64, b: f64) -> Result<f64, String> {
+		if b == 0.0 {
+			return Err("division by zero".into());
+		}
+		Ok(a / b)
+	}
+
+	pub fn parse_id(input: String) -> Result<super::Id, std::num::ParseIntError> {
+		input.parse()
+	}
+
+	pub fn parse_small(input: String) -> Result<std::primitive::i16, std::num::ParseIntError> {
+		input.parse()
+	}
+
+	#[jni(instantiate(T = i32, name = "sumInt"), instantiate(T = f64, name = "sumDouble"))]
+	pub fn sum_all<T: std::iter::Sum<T>>(values: Vec<T>) -> T {
+		values.into_iter().sum()
+	}
+
+	#[jni(instantiate(T = i32), instantiate(T = i64))]
+	pub fn largest<T: Ord + Default>(values: Vec<T>) -> T {
+		values.into_iter().max().unwrap_or_default()
+	}
+
+	#[jni(instantiate(T = Vec<i32>, name = "firstInt"))]
+	pub fn first_or_default<T: IntoIterator<Item: Default>>(values: T) -> T::Item {
+		values.into_iter().next().unwrap_or_default()
+	}
+
+	#[jni(instantiate(T = Vec<i32>, name = "lastInt"))]
+	pub fn last_or_default<T>(values: T) -> <T as IntoIterator>::Item where T: IntoIterator, <T as IntoIterator>::Item: Default {
+		values.into_iter().last().unwrap_or_default()
+	}
+
+	pub fn backtraces(enabled: bool) {
+		jni_toolbox::backtrace::set_enabled(enabled);
+	}
+
+	pub fn traced() -> Result<(), super::TracedError> {
+		Err(super::TracedError(std::backtrace::Backtrace::force_capture()))
+	}
+
+	#[jni(message = "chain")]
+	pub fn chain_message() -> Result<(), super::OuterError> {
+		Err(super::OuterError(CustomError))
+	}
+
+	#[jni(message = "debug")]
+	pub fn debug_message() -> Result<(), super::OuterError> {
+		Err(super::OuterError(CustomError))
+	}
+
+	#[jni(exception = "java.lang.IllegalStateException", message = "display")]
+	pub fn dotted_exception() -> Result<(), String> {
+		Err("thrown by name".into())
+	}
+
+	#[jni(errors = "value")]
+	pub fn checked_div(a: i32, b: i32) -> Result<i32, super::DivisionError> {
+		a.checked_div(b).ok_or(super::DivisionError)
+	}
+
+	#[jni(errors = "value")]
+	pub fn checked_nothing(fail: bool) -> Result<(), CustomError> {
+		if fail { Err(CustomError) } else { Ok(()) }
+	}
+
+	pub fn wrong_type(env: &mut jni::JNIEnv) -> Result<i64, jni::errors::Error> {
+		env.get_static_field("toolbox/Main", "ANSWER", "I")?.j()
+	}
+
+	pub fn install_mapper() {
+		let previous = jni_toolbox::error::set_jni_error_mapper(|e| match e {
+			jni::errors::Error::TryLock => Some("toolbox/CustomException"),
+			_ => None,
+		});
+		*super::PREVIOUS_MAPPER.lock().unwrap() = previous;
+	}
+
+	pub fn restore_mapper() {
+		match super::PREVIOUS_MAPPER.lock().unwrap().take() {
+			Some(mapper) => jni_toolbox::error::set_jni_error_mapper(mapper),
+			None => jni_toolbox::error::take_jni_error_mapper(),
+		};
+	}
+
+	pub fn locked() -> Result<(), jni::errors::Error> {
+		Err(jni::errors::Error::TryLock)
+	}
+
+	pub fn panicking() -> String {
+		panic!("something went wrong")
+	}
+
+	pub fn panicking_after_throw(env: &mut jni::JNIEnv) {
+		let _ = env.call_static_method("toolbox/Main", "thrower", "()V", &[]);
+		panic!("ignored a Java exception")
+	}
+
+	#[jni(name = "add")]
+	pub fn add_int(a: i32, b: i32) -> i32 {
+		a + b
+	}
+
+	#[jni(name = "add")]
+	pub fn add_long(a: i64, b: i64) -> i64 {
+		a + b
+	}
+
+	#[jni(target(name = "shared_alias"), target(class = "Main$Nested", name = "nested_shared"))]
+	pub fn shared() -> i32 {
+		7
+	}
+
+	#[jni(class = "Main$Nested")]
+	pub fn nested_answer() -> i32 {
+		42
+	}
+
+	#[jni(class = "Counter")]
+	fn max_amount() -> i32 {
+		i32::MAX
+	}
+}
+
+#[jni(package = "toolbox", class = "Main", errors = "value")]
+mod values {
+	pub fn checked_rem(a: i32, b: i32) -> Result<i32, super::DivisionError> {
+		a.checked_rem(b).ok_or(super::DivisionError)
+	}
+
+	#[jni(errors = "throw")]
+	pub fn thrown_rem(a: i32, b: i32) -> Result<i32, super::DivisionError> {
+		a.checked_rem(b).ok_or(super::DivisionError)
+	}
+}
+
+#[jni(package = "toolbox", class = "Main", name = "add", overload)]
+fn add_strings(a: String, b: String) -> String {
+	a + &b
+}
+
 #[derive(thiserror::Error, Debug)]
 #[error("some test error")]
 struct CustomError;
 
 impl JniToolboxError for CustomError {
-	fn jclass(&self) -> String {
-		"toolbox/CustomException".to_string()	
+	fn jclass(&self) -> &str {
+		"toolbox/CustomException"
 	}
 }
 
-#[jni(package = "toolbox", class = "Main")]
-fn throw_error() -> Result<(), CustomError> {
-	Err(CustomError)
+#[derive(thiserror::Error, Debug)]
+#[error("division by zero")]
+struct DivisionError;
+
+impl JniToolboxError for DivisionError {
+	fn jclass(&self) -> &str {
+		"java/lang/ArithmeticException"
+	}
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("outer test error")]
+struct OuterError(#[source] CustomError);
+
+impl JniToolboxError for OuterError {
+	fn jclass(&self) -> &str {
+		"java/lang/IllegalStateException"
+	}
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("failed with code {0}")]
+struct CodedError(i32);
+
+impl JniToolboxError for CodedError {
+	fn jclass(&self) -> &str {
+		"toolbox/CodedException"
+	}
+
+	fn message(&self) -> String {
+		"coded failure".to_string()
+	}
+
+	fn to_throwable<'j>(&self, env: &mut jni::JNIEnv<'j>) -> Result<jni::objects::JThrowable<'j>, jni::errors::Error> {
+		let msg = env.new_string(self.message())?;
+		let obj = env.new_object(self.jclass(), "(Ljava/lang/String;I)V", &[(&msg).into(), self.0.into()])?;
+		Ok(obj.into())
+	}
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("failed with a cause")]
+struct CodedCauseError(#[source] CustomError);
+
+impl JniToolboxError for CodedCauseError {
+	fn jclass(&self) -> &str {
+		"toolbox/CodedException"
+	}
+
+	fn to_throwable<'j>(&self, env: &mut jni::JNIEnv<'j>) -> Result<jni::objects::JThrowable<'j>, jni::errors::Error> {
+		let msg = env.new_string(self.message())?;
+		let obj = env.new_object(self.jclass(), "(Ljava/lang/String;I)V", &[(&msg).into(), 500.into()])?;
+		Ok(obj.into())
+	}
+}
+
+#[derive(thiserror::Error, Debug, JniToolboxError)]
+#[jni_error(class = "java.lang.IllegalArgumentException")]
+enum DerivedError {
+	#[error("bad argument")]
+	BadArgument,
+	#[error("index {0} out of bounds")]
+	#[jni_error(class = "java/lang/IndexOutOfBoundsException")]
+	OutOfBounds(i32),
+	#[error(transparent)]
+	#[jni_error(transparent)]
+	Custom(CustomError),
+}
+
+#[derive(thiserror::Error, Debug, JniToolboxError, FromJavaException)]
+enum CallbackError {
+	#[error(transparent)]
+	#[java_exception("java.io.IOException")]
+	Io(JavaException),
+	#[error(transparent)]
+	#[java_exception]
+	Other(JavaException),
+	#[error(transparent)]
+	#[jni_error(transparent)]
+	Jni(#[from] jni::errors::Error),
+}
+
+// thiserror would need nightly to handle the backtrace field
+#[derive(Debug, JniToolboxError)]
+#[jni_error(class = "toolbox/CustomException")]
+struct TracedError(std::backtrace::Backtrace);
+
+impl std::fmt::Display for TracedError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "traced test error")
+	}
+}
+
+impl std::error::Error for TracedError {}
+
+type Id = i64;
+
+/// The mapper replaced by `install_mapper`, put back by `restore_mapper`.
+static PREVIOUS_MAPPER: std::sync::Mutex<Option<jni_toolbox::error::JniErrorMapper>> = std::sync::Mutex::new(None);
+
+/// Converts `long` milliseconds since the epoch to and from `SystemTime`.
+mod epoch_millis {
+	use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+	pub type From<'j> = jni::sys::jlong;
+	pub type Ret<'j> = jni::sys::jlong;
+
+	pub fn from_java(_: &mut jni::JNIEnv, value: jni::sys::jlong) -> Result<SystemTime, jni::errors::Error> {
+		let millis = u64::try_from(value).map_err(|_| jni::errors::Error::JniCall(jni::errors::JniError::InvalidArguments))?;
+		Ok(UNIX_EPOCH + Duration::from_millis(millis))
+	}
+
+	pub fn into_java(value: SystemTime, _: &mut jni::JNIEnv) -> Result<jni::sys::jlong, jni::errors::Error> {
+		let millis = value.duration_since(UNIX_EPOCH).map_err(|_| jni::errors::Error::JniCall(jni::errors::JniError::InvalidArguments))?.as_millis();
+		Ok(millis as jni::sys::jlong)
+	}
+}
+
+/// Any object, received through its `toString()`.
+struct Described(String);
+
+impl<'j> jni_toolbox::FromJava<'j> for Described {
+	type From = jni::objects::JObject<'j>;
+
+	fn from_java(env: &mut jni::JNIEnv<'j>, value: Self::From) -> Result<Self, jni::errors::Error> {
+		let string = env.call_method(&value, "toString", "()Ljava/lang/String;", &[])?.l()?;
+		Ok(Self(env.get_string(&string.into())?.into()))
+	}
+}
+
+/// Stands for an error type from another crate, which can't implement JniToolboxError.
+#[derive(thiserror::Error, Debug)]
+#[error("foreign failure")]
+struct ForeignError(#[source] std::io::Error);
+
+#[derive(Default)]
+struct Counter {
+	value: i32,
+}
+
+#[jni(package = "toolbox", class = "Counter")]
+fn create() -> i64 {
+	jni_toolbox::peer::register(Counter::default())
+}
+
+#[jni(package = "toolbox", class = "Counter")]
+fn free(ptr: i64) -> Result<(), jni_toolbox::peer::PeerError> {
+	jni_toolbox::peer::release::<Counter>(ptr)
+}
+
+impl Counter {
+	#[jni(package = "toolbox", class = "Counter")]
+	fn increment(&mut self, amount: i32) -> i32 {
+		self.value += amount;
+		self.value
+	}
+
+	#[jni(package = "toolbox", class = "Counter", handle)]
+	fn peek(&self) -> i32 {
+		self.value
+	}
+}
+
+#[derive(Default)]
+struct Gauge {
+	value: i32,
+}
+
+#[jni(package = "toolbox", class = "Gauge")]
+impl Gauge {
+	pub fn create() -> i64 {
+		jni_toolbox::peer::register(Gauge::new())
+	}
+
+	pub fn free(ptr: i64) -> Result<(), jni_toolbox::peer::PeerError> {
+		jni_toolbox::peer::release::<Gauge>(ptr)
+	}
+
+	pub fn add(&mut self, amount: i32) -> i32 {
+		self.value += amount;
+		self.value
+	}
+
+	#[jni(handle)]
+	pub fn peek(&self) -> i32 {
+		self.value
+	}
+
+	pub fn nested(&mut self, #[env] env: &mut jni::JNIEnv, #[this] this: &jni::objects::JObject) -> Result<i32, jni::errors::Error> {
+		env.call_method(this, "add", "(I)I", &[1.into()])?.i()
+	}
+
+	#[jni(skip)]
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+#[jni(package = "toolbox", class = "Registered", register)]
+mod registered {
+	pub fn multiply(a: i32, b: i32) -> i32 {
+		a * b
+	}
+
+	#[jni(errors = "value", exception = "java/lang/ArithmeticException", message = "display")]
+	pub fn divide(a: i32, b: i32) -> Result<i32, String> {
+		a.checked_div(b).ok_or_else(|| "division by zero".to_string())
+	}
+
+	pub struct Greeter {
+		greeting: String,
+	}
+
+	#[jni]
+	impl Greeter {
+		pub fn greeter(greeting: String) -> i64 {
+			jni_toolbox::peer::register(Greeter { greeting })
+		}
+
+		#[jni(handle)]
+		pub fn greet(&self, name: String) -> String {
+			format!("{} {name}", self.greeting)
+		}
+	}
 }
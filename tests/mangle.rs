@@ -0,0 +1,75 @@
+use jni_toolbox::mangle;
+
+#[test]
+fn escape() {
+	let table = [
+		("", ""),
+		("sum", "sum"),
+		("getX2", "getX2"),
+		("to_vec", "to_1vec"),
+		("__init", "_1_1init"),
+		("com.example", "com_example"),
+		("com/example", "com_example"),
+		("my_pkg.sub_pkg", "my_1pkg_sub_1pkg"),
+		("Outer$Inner", "Outer_00024Inner"),
+		("Outer$1", "Outer_000241"),
+		("Ljava/lang/String;", "Ljava_lang_String_2"),
+		("[I", "_3I"),
+		("[[Ljava/lang/Object;", "_3_3Ljava_lang_Object_2"),
+		("café", "caf_000e9"),
+		("ÀÉ", "_000c0_000c9"),
+		("名前", "_0540d_0524d"),
+		("a-b", "a_0002db"),
+		("𝔘", "_0d835_0dd18"), // outside the BMP, encoded as a surrogate pair
+	];
+	for (input, expected) in table {
+		assert_eq!(mangle::escape(input), expected, "escaping {input:?}");
+	}
+}
+
+#[test]
+fn class() {
+	let table = [
+		("", "Main", "Main"),
+		("toolbox", "Main", "toolbox_Main"),
+		("com.example", "Main", "com_example_Main"),
+		("com/example", "Main", "com_example_Main"),
+		("com.my_app", "My_Class", "com_my_1app_My_1Class"),
+		("com.example", "Outer$Inner", "com_example_Outer_00024Inner"),
+		("com.exämple", "Main", "com_ex_000e4mple_Main"),
+	];
+	for (package, class, expected) in table {
+		assert_eq!(mangle::class(package, class), expected, "mangling {package:?} {class:?}");
+	}
+}
+
+#[test]
+fn short_name() {
+	let table = [
+		("toolbox", "Main", "sum", "Java_toolbox_Main_sum"),
+		("toolbox", "Main", "to_vec", "Java_toolbox_Main_to_1vec"),
+		("", "Main", "sum", "Java_Main_sum"),
+		("mp.code", "Client", "connect", "Java_mp_code_Client_connect"),
+		("com.example", "Outer$Inner", "run", "Java_com_example_Outer_00024Inner_run"),
+		("com.example", "Main", "grüß", "Java_com_example_Main_gr_000fc_000df"),
+	];
+	for (package, class, method, expected) in table {
+		assert_eq!(mangle::short_name(package, class, method), expected);
+	}
+}
+
+#[test]
+fn long_name() {
+	let table = [
+		("toolbox", "Main", "sum", "(II)I", "Java_toolbox_Main_sum__II"),
+		("toolbox", "Main", "sum", "II", "Java_toolbox_Main_sum__II"),
+		("toolbox", "Main", "run", "()V", "Java_toolbox_Main_run__"),
+		("toolbox", "Main", "concat", "(Ljava/lang/String;Ljava/lang/String;)Ljava/lang/String;", "Java_toolbox_Main_concat__Ljava_lang_String_2Ljava_lang_String_2"),
+		("toolbox", "Main", "sum_all", "([I)I", "Java_toolbox_Main_sum_1all___3I"),
+		("toolbox", "Main", "names", "([Ljava/lang/String;)V", "Java_toolbox_Main_names___3Ljava_lang_String_2"),
+		("com.example", "Outer$Inner", "set", "(Lcom/example/Outer$Inner;)V", "Java_com_example_Outer_00024Inner_set__Lcom_example_Outer_00024Inner_2"),
+	];
+	for (package, class, method, signature, expected) in table {
+		assert_eq!(mangle::long_name(package, class, method, signature), expected);
+	}
+}